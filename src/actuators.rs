@@ -3,11 +3,91 @@ use robstride::{
     ActuatorConfiguration, ActuatorType, CH341Transport, ControlConfig, SocketCanTransport,
     Supervisor, TransportType,
 };
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::time::Instant;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use tracing::trace;
 
+/// Named motor fault, decoded from the feedback status word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActuatorFault {
+    OverTemperature,
+    OverCurrent,
+    UnderVoltage,
+    EncoderFault,
+    HallFault,
+    /// Unrecognized status bits, as the combined mask of every set bit not
+    /// named above, kept around (rather than dropped) so operators still
+    /// see something changed.
+    Other(u16),
+}
+
+impl ActuatorFault {
+    /// Serious faults auto-disable the motor; the rest are surfaced for
+    /// visibility but don't stop commands.
+    pub fn is_serious(&self) -> bool {
+        matches!(
+            self,
+            ActuatorFault::OverTemperature
+                | ActuatorFault::OverCurrent
+                | ActuatorFault::UnderVoltage
+                | ActuatorFault::EncoderFault
+                | ActuatorFault::HallFault
+        )
+    }
+}
+
+impl std::fmt::Display for ActuatorFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActuatorFault::OverTemperature => write!(f, "over-temperature"),
+            ActuatorFault::OverCurrent => write!(f, "over-current"),
+            ActuatorFault::UnderVoltage => write!(f, "under-voltage"),
+            ActuatorFault::EncoderFault => write!(f, "encoder fault"),
+            ActuatorFault::HallFault => write!(f, "hall sensor fault"),
+            ActuatorFault::Other(bits) => write!(f, "unrecognized fault (bits {:#06x})", bits),
+        }
+    }
+}
+
+/// Decodes a feedback status word into named faults, one per recognized set
+/// bit; any unrecognized set bits are reported together as a single `Other`
+/// carrying their mask.
+fn decode_faults(status: u16) -> Vec<ActuatorFault> {
+    const OVER_TEMPERATURE: u16 = 1 << 0;
+    const OVER_CURRENT: u16 = 1 << 1;
+    const UNDER_VOLTAGE: u16 = 1 << 2;
+    const ENCODER_FAULT: u16 = 1 << 3;
+    const HALL_FAULT: u16 = 1 << 4;
+
+    let mut faults = Vec::new();
+    if status & OVER_TEMPERATURE != 0 {
+        faults.push(ActuatorFault::OverTemperature);
+    }
+    if status & OVER_CURRENT != 0 {
+        faults.push(ActuatorFault::OverCurrent);
+    }
+    if status & UNDER_VOLTAGE != 0 {
+        faults.push(ActuatorFault::UnderVoltage);
+    }
+    if status & ENCODER_FAULT != 0 {
+        faults.push(ActuatorFault::EncoderFault);
+    }
+    if status & HALL_FAULT != 0 {
+        faults.push(ActuatorFault::HallFault);
+    }
+    let known = OVER_TEMPERATURE | OVER_CURRENT | UNDER_VOLTAGE | ENCODER_FAULT | HALL_FAULT;
+    let unknown = status & !known;
+    if unknown != 0 {
+        faults.push(ActuatorFault::Other(unknown));
+    }
+    faults
+}
+
 #[cfg(feature = "json_logging")]
 use robstride::JsonLogger;
 
@@ -19,6 +99,28 @@ pub struct ActuatorCommand {
     pub torque: Option<f64>,
 }
 
+/// One scheduled batch in a `load_trajectory` upload: `commands` is
+/// dispatched `time_offset` after `play_trajectory` starts, analogous to a
+/// DMA engine's preloaded, timestamped instruction stream.
+#[derive(Clone)]
+pub struct TimedCommand {
+    pub time_offset: Duration,
+    pub commands: Vec<ActuatorCommand>,
+}
+
+/// Outcome of dispatching one `TimedCommand`, reported on the channel
+/// returned by `play_trajectory`.
+pub struct TrajectoryStepResult {
+    pub index: usize,
+    pub results: Vec<ActionResult>,
+    /// How long after the scheduled offset dispatch actually happened.
+    pub jitter: Duration,
+    /// Set when the scheduled time had already elapsed before the
+    /// background task could get to this step, i.e. it fired late rather
+    /// than on time.
+    pub underrun: bool,
+}
+
 pub struct ConfigureRequest {
     pub actuator_id: u32,
     pub kp: Option<f32>,
@@ -42,6 +144,7 @@ pub struct ActionResponse {
     pub error: Option<String>,
 }
 
+#[derive(Clone)]
 pub struct ActuatorState {
     pub actuator_id: u32,
     pub position: Option<f64>,
@@ -49,10 +152,35 @@ pub struct ActuatorState {
     pub torque: Option<f64>,
     pub temperature: Option<f64>,
     pub online: bool,
+    /// Firmware revision read off the bus during the discovery pass in
+    /// `Actuator::new`, so callers can gate behavior on controller version.
+    pub firmware: Option<String>,
+    /// Faults latched in the feedback status word; see `ActuatorFault::is_serious`
+    /// for which ones auto-disable the motor.
+    pub faults: Vec<ActuatorFault>,
 }
 
 pub struct Actuator {
     supervisor: Arc<Mutex<Supervisor>>,
+    /// Firmware revision per actuator ID, populated by the discovery pass
+    /// in `new`.
+    firmware: Arc<Mutex<HashMap<u32, String>>>,
+    /// Motors auto-disabled after latching a serious fault; `command_actuators`
+    /// rejects further commands to these IDs until the fault is cleared and
+    /// the process restarted.
+    fault_disabled: Arc<Mutex<HashMap<u32, ActuatorFault>>>,
+    /// Latest decoded state per actuator, kept fresh by the background
+    /// polling task spawned in `new`, so `get_actuators_state` is a cache
+    /// read instead of a lock-and-poll.
+    feedback_cache: Arc<RwLock<HashMap<u32, ActuatorState>>>,
+    /// Fans out every cache update; `subscribe_feedback` taps this.
+    feedback_tx: broadcast::Sender<ActuatorState>,
+    /// Buffer loaded by `load_trajectory`, drained by the background task
+    /// `play_trajectory` spawns.
+    trajectory: Arc<Mutex<Option<Vec<TimedCommand>>>>,
+    /// Set while a trajectory playback task is running; `stop_trajectory`
+    /// clears it so the task's next wakeup exits instead of dispatching.
+    trajectory_running: Arc<AtomicBool>,
     #[cfg(feature = "json_logging")]
     _json_logger: Option<Arc<JsonLogger>>,
 }
@@ -87,6 +215,7 @@ impl Actuator {
         }
 
         // Scan for motors on each port
+        let mut firmware = HashMap::new();
         for port in &ports {
             let discovered_ids = supervisor.scan_bus(0xFD, port, actuators_config).await?;
             tracing::info!("Discovered IDs on {}: {:?}", port, discovered_ids);
@@ -106,10 +235,26 @@ impl Actuator {
                 );
             }
 
-            // Mark found configured motors
-            for (idx, (motor_id, _)) in actuators_config.iter().enumerate() {
+            // Mark found configured motors, identifying each one (ping ->
+            // read model/firmware, Dynamixel-style) and cross-checking it
+            // against the configured actuator type so a RobStride04 wired
+            // where a 02 is expected fails loudly instead of being
+            // commanded with the wrong gains.
+            for (idx, (motor_id, config)) in actuators_config.iter().enumerate() {
                 if discovered_ids.contains(motor_id) {
                     found_motors[idx] = true;
+
+                    let (actuator_type, firmware_version) =
+                        supervisor.read_actuator_info(*motor_id).await?;
+                    if actuator_type != config.actuator_type {
+                        return Err(eyre::eyre!(
+                            "Actuator {} identified as {:?} but configured as {:?}",
+                            motor_id,
+                            actuator_type,
+                            config.actuator_type
+                        ));
+                    }
+                    firmware.insert(*motor_id as u32, firmware_version);
                 }
             }
         }
@@ -151,33 +296,305 @@ impl Actuator {
             None
         };
 
+        let (feedback_tx, _) = broadcast::channel(256);
         let actuator = Self {
             supervisor: Arc::new(Mutex::new(supervisor)),
+            firmware: Arc::new(Mutex::new(firmware)),
+            fault_disabled: Arc::new(Mutex::new(HashMap::new())),
+            feedback_cache: Arc::new(RwLock::new(HashMap::new())),
+            feedback_tx,
+            trajectory: Arc::new(Mutex::new(None)),
+            trajectory_running: Arc::new(AtomicBool::new(false)),
             #[cfg(feature = "json_logging")]
             _json_logger: json_logger,
         };
 
+        // Drains feedback for every configured actuator in a single
+        // background task, instead of every caller taking the supervisor
+        // lock to trigger-and-poll a read itself.
+        //
+        // `robstride`'s transports don't expose a raw fd or any other
+        // readiness notification we could drive off a reactor, so this
+        // still falls back to fixed-interval polling rather than the
+        // readiness-driven read path that would avoid the command lock
+        // entirely (see `kinematics.rs`/`state_estimator.rs` for the same
+        // kind of external-crate gap). What we *can* do without that API is
+        // bound how much this steady background poll gets in the way of
+        // `command_actuators`: it uses `try_lock` and simply skips an
+        // actuator for this cycle if the lock is currently held, so a
+        // command in flight is never delayed waiting on a feedback poll.
+        let poll_ids: Vec<u32> = actuators_config.iter().map(|(id, _)| *id as u32).collect();
+        let supervisor = actuator.supervisor.clone();
+        let firmware = actuator.firmware.clone();
+        let fault_disabled = actuator.fault_disabled.clone();
+        let feedback_cache = actuator.feedback_cache.clone();
+        let feedback_tx = actuator.feedback_tx.clone();
+        tokio::spawn(async move {
+            loop {
+                for &id in &poll_ids {
+                    let Ok(mut guard) = supervisor.try_lock() else {
+                        // Command path holds the lock right now; don't block
+                        // it waiting on a poll, just catch this actuator on
+                        // the next cycle.
+                        continue;
+                    };
+                    let state =
+                        Actuator::poll_actuator_state(&mut guard, &firmware, &fault_disabled, id)
+                            .await;
+                    drop(guard);
+                    feedback_cache.write().await.insert(id, state.clone());
+                    // No subscribers is the common case when nothing has
+                    // called `subscribe_feedback` yet; not an error.
+                    let _ = feedback_tx.send(state);
+                }
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }
+        });
+
         Ok(actuator)
     }
 
+    /// Triggers and reads back one actuator's feedback, decoding faults and
+    /// auto-disabling on a serious one. Takes an already-locked `supervisor`
+    /// so the background poll task (which only wants this actuator's
+    /// feedback while it can acquire the lock without blocking) and any
+    /// future one-off caller can share the same logic.
+    async fn poll_actuator_state(
+        supervisor: &mut Supervisor,
+        firmware: &Arc<Mutex<HashMap<u32, String>>>,
+        fault_disabled: &Arc<Mutex<HashMap<u32, ActuatorFault>>>,
+        id: u32,
+    ) -> ActuatorState {
+        if let Err(e) = supervisor.request_feedback(id as u8).await {
+            tracing::warn!("Failed to request feedback for actuator {}: {}", id, e);
+        }
+
+        if let Ok(Some((feedback, _))) = supervisor.get_feedback(id as u8).await {
+            let faults = decode_faults(feedback.fault_status);
+            if let Some(serious) = faults.iter().find(|f| f.is_serious()) {
+                let mut fault_disabled = fault_disabled.lock().await;
+                if !fault_disabled.contains_key(&id) {
+                    tracing::error!(
+                        "Actuator {} latched serious fault {}, disabling",
+                        id,
+                        serious
+                    );
+                    if let Err(e) = supervisor.disable(id as u8, true).await {
+                        tracing::error!("Failed to disable faulted actuator {}: {}", id, e);
+                    }
+                    fault_disabled.insert(id, *serious);
+                }
+            }
+
+            ActuatorState {
+                actuator_id: id,
+                online: true,
+                position: Some(feedback.angle as f64),
+                velocity: Some(feedback.velocity as f64),
+                torque: Some(feedback.torque as f64),
+                temperature: Some(feedback.temperature as f64),
+                firmware: firmware.lock().await.get(&id).cloned(),
+                faults,
+            }
+        } else {
+            ActuatorState {
+                actuator_id: id,
+                online: false,
+                position: None,
+                velocity: None,
+                torque: None,
+                temperature: None,
+                firmware: firmware.lock().await.get(&id).cloned(),
+                faults: Vec::new(),
+            }
+        }
+    }
+
+    /// Subscribes to every future feedback update for `actuator_ids`,
+    /// fanned out from the same background poll that keeps
+    /// `get_actuators_state`'s cache fresh.
+    pub fn subscribe_feedback(&self, actuator_ids: Vec<u32>) -> impl Stream<Item = ActuatorState> {
+        let ids: HashSet<u32> = actuator_ids.into_iter().collect();
+        BroadcastStream::new(self.feedback_tx.subscribe())
+            .filter_map(|item| item.ok())
+            .filter(move |state| ids.contains(&state.actuator_id))
+    }
+
+    /// Scans each port with no prior configuration and builds an actuator
+    /// config list straight from what identifies itself on the bus, so a new
+    /// robot's joint set doesn't require hand-editing `create_kbot_actuators`.
+    pub async fn discover_actuators(ports: Vec<&str>) -> Result<Vec<(u8, ActuatorConfiguration)>> {
+        let max_angle_change = 5.0f32; // Percent
+        let max_velocity = 10.0f32.to_radians();
+        let command_rate_hz = 50.0;
+
+        let mut supervisor = Supervisor::new(Duration::from_millis(100))?;
+        for port in &ports {
+            let transport = match port {
+                p if p.starts_with("/dev/tty") => {
+                    TransportType::CH341(CH341Transport::new(p.to_string()).await?)
+                }
+                p if p.starts_with("can") => {
+                    TransportType::SocketCAN(SocketCanTransport::new(p.to_string()).await?)
+                }
+                _ => return Err(eyre::eyre!("Invalid port: {}", port)),
+            };
+            supervisor.add_transport(port.to_string(), transport).await?;
+        }
+
+        let mut discovered = Vec::new();
+        for port in &ports {
+            for id in supervisor.scan_bus(0xFD, port, &[]).await? {
+                let (actuator_type, firmware_version) = supervisor.read_actuator_info(id).await?;
+                tracing::info!(
+                    "Discovered actuator {} on {}: {:?} (firmware {})",
+                    id,
+                    port,
+                    actuator_type,
+                    firmware_version
+                );
+                discovered.push((
+                    id,
+                    ActuatorConfiguration {
+                        actuator_type,
+                        max_angle_change: Some(max_angle_change),
+                        max_velocity: Some(max_velocity),
+                        command_rate_hz: Some(command_rate_hz),
+                    },
+                ));
+            }
+        }
+        Ok(discovered)
+    }
+
     pub async fn command_actuators(
         &self,
         commands: Vec<ActuatorCommand>,
     ) -> Result<Vec<ActionResult>> {
         let uuid = uuid::Uuid::new_v4();
         trace!("actuator::command_actuators::START uuid={}", uuid);
+        let results =
+            Actuator::dispatch_commands(&self.supervisor, &self.fault_disabled, commands).await;
+        trace!("actuator::command_actuators::END uuid={}", uuid);
+        Ok(results)
+    }
+
+    /// Group/sync-write variant of `command_actuators`: instead of awaiting
+    /// `supervisor.command` once per actuator while holding the lock (N
+    /// serialized round trips smeared across the tick), this hands the
+    /// whole batch to `Supervisor::command_batch`, which groups by
+    /// transport/port internally and flushes one packet per bus, so every
+    /// motor on a bus receives its setpoint within the same tick. Matters
+    /// for legged locomotion, where staggered setpoints across joints cause
+    /// instability. Keeps the same per-ID `ActionResult` contract as
+    /// `command_actuators`.
+    pub async fn command_actuators_sync(
+        &self,
+        commands: Vec<ActuatorCommand>,
+    ) -> Result<Vec<ActionResult>> {
+        let uuid = uuid::Uuid::new_v4();
+        trace!("actuator::command_actuators_sync::START uuid={}", uuid);
+
         let mut results = vec![];
-        let mut supervisor = self.supervisor.lock().await;
+        let mut batch = vec![];
+        {
+            let fault_disabled = self.fault_disabled.lock().await;
+            for command in commands {
+                if let Some(fault) = fault_disabled.get(&command.actuator_id) {
+                    results.push(ActionResult {
+                        actuator_id: command.actuator_id,
+                        success: false,
+                        error: Some(format!(
+                            "Actuator {} is disabled due to a latched fault: {}",
+                            command.actuator_id, fault
+                        )),
+                    });
+                    continue;
+                }
+
+                let position = match command.position.map(|p| p as f32) {
+                    Some(position) => position,
+                    None => {
+                        results.push(ActionResult {
+                            actuator_id: command.actuator_id,
+                            success: false,
+                            error: Some(format!(
+                                "No position specified for actuator {}",
+                                command.actuator_id
+                            )),
+                        });
+                        continue;
+                    }
+                };
+
+                batch.push((
+                    command.actuator_id as u8,
+                    position,
+                    command.velocity.map(|v| v as f32).unwrap_or(0.0),
+                    command.torque.map(|t| t as f32).unwrap_or(0.0),
+                ));
+            }
+        }
+
+        if !batch.is_empty() {
+            let mut supervisor = self.supervisor.lock().await;
+            for (motor_id, result) in supervisor.command_batch(&batch).await? {
+                results.push(ActionResult {
+                    actuator_id: motor_id as u32,
+                    success: result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                });
+            }
+        }
+
+        trace!("actuator::command_actuators_sync::END uuid={}", uuid);
+        Ok(results)
+    }
+
+    /// Dispatches one batch of commands against `supervisor`, rejecting any
+    /// whose actuator is fault-disabled. Shared by `command_actuators` and
+    /// the trajectory-playback background task in `play_trajectory`.
+    async fn dispatch_commands(
+        supervisor: &Arc<Mutex<Supervisor>>,
+        fault_disabled: &Arc<Mutex<HashMap<u32, ActuatorFault>>>,
+        commands: Vec<ActuatorCommand>,
+    ) -> Vec<ActionResult> {
+        let mut results = vec![];
+        let mut supervisor = supervisor.lock().await;
+        let fault_disabled = fault_disabled.lock().await;
 
         for command in commands {
+            if let Some(fault) = fault_disabled.get(&command.actuator_id) {
+                results.push(ActionResult {
+                    actuator_id: command.actuator_id,
+                    success: false,
+                    error: Some(format!(
+                        "Actuator {} is disabled due to a latched fault: {}",
+                        command.actuator_id, fault
+                    )),
+                });
+                continue;
+            }
+
             let motor_id = command.actuator_id as u8;
+            let position = match command.position.map(|p| p as f32) {
+                Some(position) => position,
+                None => {
+                    results.push(ActionResult {
+                        actuator_id: command.actuator_id,
+                        success: false,
+                        error: Some(format!(
+                            "No position specified for actuator {}",
+                            command.actuator_id
+                        )),
+                    });
+                    continue;
+                }
+            };
             let result = supervisor
                 .command(
                     motor_id,
-                    command.position.map(|p| p as f32).ok_or(eyre::eyre!(
-                        "No position specified for actuator {}",
-                        command.actuator_id
-                    ))?,
+                    position,
                     command.velocity.map(|v| v as f32).unwrap_or(0.0), // We assume default target velocity is 0 if not specified
                     command.torque.map(|t| t as f32).unwrap_or(0.0), // We assume default target torque is 0 if not specified
                 )
@@ -189,8 +606,84 @@ impl Actuator {
                 error: result.err().map(|e| e.to_string()),
             });
         }
-        trace!("actuator::command_actuators::END uuid={}", uuid);
-        Ok(results)
+        results
+    }
+
+    /// Loads a trajectory for `play_trajectory` to dispatch, replacing any
+    /// previously loaded (but not yet started) one.
+    pub async fn load_trajectory(&self, trajectory: Vec<TimedCommand>) {
+        *self.trajectory.lock().await = Some(trajectory);
+    }
+
+    /// Dispatches the trajectory loaded by `load_trajectory` from a
+    /// background task, one `TimedCommand` per scheduled offset, using a
+    /// monotonic clock (`tokio::time::Instant`) so playback isn't skewed by
+    /// wall-clock adjustments — a direct-memory-playback style engine that
+    /// preloads a timestamped instruction stream and replays it
+    /// deterministically instead of relying on the caller's own loop timing.
+    ///
+    /// Each step's result, including how far dispatch landed past its
+    /// scheduled offset (`jitter`) and whether it had already missed that
+    /// offset before dispatch could even start (`underrun`), is sent on the
+    /// returned channel as it happens; the channel closes when the
+    /// trajectory finishes, the receiver is dropped, or `stop_trajectory` is
+    /// called.
+    pub async fn play_trajectory(&self) -> Result<mpsc::Receiver<TrajectoryStepResult>> {
+        if self.trajectory_running.swap(true, Ordering::SeqCst) {
+            return Err(eyre::eyre!("a trajectory is already playing"));
+        }
+
+        let Some(steps) = self.trajectory.lock().await.take() else {
+            self.trajectory_running.store(false, Ordering::SeqCst);
+            return Err(eyre::eyre!("no trajectory loaded"));
+        };
+
+        let (tx, rx) = mpsc::channel(steps.len().max(1));
+        let supervisor = self.supervisor.clone();
+        let fault_disabled = self.fault_disabled.clone();
+        let running = self.trajectory_running.clone();
+
+        tokio::spawn(async move {
+            let start = Instant::now();
+            for (index, step) in steps.into_iter().enumerate() {
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let deadline = start + step.time_offset;
+                let underrun = Instant::now() > deadline;
+                tokio::time::sleep_until(deadline).await;
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let results =
+                    Actuator::dispatch_commands(&supervisor, &fault_disabled, step.commands).await;
+                let jitter = Instant::now().saturating_duration_since(deadline);
+
+                if tx
+                    .send(TrajectoryStepResult {
+                        index,
+                        results,
+                        jitter,
+                        underrun,
+                    })
+                    .await
+                    .is_err()
+                {
+                    break; // Receiver dropped; no point dispatching the rest.
+                }
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+
+        Ok(rx)
+    }
+
+    /// Stops a running `play_trajectory` task before it reaches the end of
+    /// the loaded trajectory; a no-op if nothing is playing.
+    pub fn stop_trajectory(&self) {
+        self.trajectory_running.store(false, Ordering::SeqCst);
     }
 
     pub async fn configure_actuator(&self, config: ConfigureRequest) -> Result<ActionResponse> {
@@ -243,35 +736,31 @@ impl Actuator {
         Ok(())
     }
 
+    /// Latest cached state per actuator, kept fresh by the background
+    /// polling task in `new` — a cache read rather than a lock-and-poll, so
+    /// it doesn't contend with `command_actuators` at high command rates.
     pub async fn get_actuators_state(&self, actuator_ids: Vec<u32>) -> Result<Vec<ActuatorState>> {
         let uuid = uuid::Uuid::new_v4();
         trace!("actuator::get_actuators_state::START uuid={}", uuid);
-        let mut responses = vec![];
-
-        // Reads the latest feedback from each actuator.
-        let supervisor = self.supervisor.lock().await;
-        for id in actuator_ids {
-            if let Ok(Some((feedback, _))) = supervisor.get_feedback(id as u8).await {
-                responses.push(ActuatorState {
-                    actuator_id: id,
-                    online: true,
-                    position: Some(feedback.angle as f64),
-                    velocity: Some(feedback.velocity as f64),
-                    torque: Some(feedback.torque as f64),
-                    temperature: Some(feedback.temperature as f64),
-                });
-            } else {
-                tracing::warn!("No feedback or error for actuator ID: {}", id);
-                responses.push(ActuatorState {
-                    actuator_id: id,
-                    online: false,
-                    position: None,
-                    velocity: None,
-                    torque: None,
-                    temperature: None,
-                });
-            }
-        }
+        let cache = self.feedback_cache.read().await;
+        let responses = actuator_ids
+            .into_iter()
+            .map(|id| {
+                cache.get(&id).cloned().unwrap_or_else(|| {
+                    tracing::warn!("No cached feedback yet for actuator ID: {}", id);
+                    ActuatorState {
+                        actuator_id: id,
+                        online: false,
+                        position: None,
+                        velocity: None,
+                        torque: None,
+                        temperature: None,
+                        firmware: None,
+                        faults: Vec::new(),
+                    }
+                })
+            })
+            .collect();
         trace!("actuator::get_actuators_state::END uuid={}", uuid);
         Ok(responses)
     }
@@ -484,4 +973,54 @@ impl Actuator {
             ),
         ]
     }
+
+    /// Builds an `(id, ActuatorConfiguration)` list like
+    /// `create_kbot_actuators`, but driven by a `config::GainTable`'s
+    /// `actuators` entries instead of the compiled constants, so a gain file
+    /// can describe a different joint set without a recompile.
+    ///
+    /// The actuator type isn't part of the gain-table schema, so it's
+    /// inferred from the joint name's `_NN` suffix, matching the naming
+    /// convention `constants::ACTUATOR_NAME_TO_ID` already uses (e.g.
+    /// `dof_left_wrist_00` is a `RobStride00`). For the compiled default
+    /// names this differs from `create_kbot_actuators` on the wrists (IDs 15
+    /// and 25): their `_00` names give `RobStride00`, where
+    /// `create_kbot_actuators` configures `RobStride02`.
+    pub fn kbot_actuators_from_gain_entries(
+        entries: &[crate::config::ActuatorGainEntry],
+    ) -> Result<Vec<(u8, ActuatorConfiguration)>> {
+        let max_angle_change = 5.0f32; // Percent
+        let max_velocity_default = 10.0f32.to_radians();
+        let command_rate_hz = 50.0;
+
+        entries
+            .iter()
+            .map(|entry| {
+                let actuator_type = if entry.name.ends_with("_00") {
+                    ActuatorType::RobStride00
+                } else if entry.name.ends_with("_02") {
+                    ActuatorType::RobStride02
+                } else if entry.name.ends_with("_03") {
+                    ActuatorType::RobStride03
+                } else if entry.name.ends_with("_04") {
+                    ActuatorType::RobStride04
+                } else {
+                    return Err(eyre::eyre!(
+                        "Cannot infer actuator type from joint name: {}",
+                        entry.name
+                    ));
+                };
+
+                Ok((
+                    entry.id as u8,
+                    ActuatorConfiguration {
+                        actuator_type,
+                        max_angle_change: Some(max_angle_change),
+                        max_velocity: Some(entry.max_velocity.unwrap_or(max_velocity_default)),
+                        command_rate_hz: Some(command_rate_hz),
+                    },
+                ))
+            })
+            .collect()
+    }
 }
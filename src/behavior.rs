@@ -0,0 +1,117 @@
+//! Startup and idle keyframe state machine. On boot, steps through a
+//! configurable "startup" keyframe sequence before handing control to the
+//! policy, and after a configurable idle timeout with zero locomotion
+//! commands transitions to an "idle" keyframe, returning to active control
+//! on the next nonzero command. Each step only writes its keyframe index
+//! into the `KEYFRAME_INDEX` command slot for the step's duration; this
+//! module moves no joints itself, and while a sequence owns the slot
+//! `ModelRuntime` holds the last commanded joint positions. The sequences
+//! are declared in the same config file as the actuator gains (see
+//! `config::BehaviorConfig`), so operators can customize the startup/idle
+//! keyframes without code changes.
+
+use std::time::Instant;
+
+use crate::config::BehaviorConfig;
+use crate::keyboard;
+
+/// `keyboard::get_commands()` slot index for the keyframe command.
+const KEYFRAME_SLOT: usize = 7;
+/// Slots that count as "locomotion" for idle detection: x, y, yaw_rate.
+const LOCOMOTION_SLOTS: [usize; 3] = [0, 1, 2];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BehaviorState {
+    /// Playing the configured startup keyframe sequence.
+    Startup,
+    /// Handing control to the policy.
+    Active,
+    /// Holding the idle keyframe after a timeout with no locomotion command.
+    Idle,
+}
+
+pub struct BehaviorMachine {
+    config: BehaviorConfig,
+    state: BehaviorState,
+    step_started: Instant,
+    startup_index: usize,
+    last_active: Instant,
+}
+
+impl BehaviorMachine {
+    pub fn new(config: BehaviorConfig) -> Self {
+        let now = Instant::now();
+        let state = if config.startup.is_empty() {
+            BehaviorState::Active
+        } else {
+            BehaviorState::Startup
+        };
+
+        Self {
+            config,
+            state,
+            step_started: now,
+            startup_index: 0,
+            last_active: now,
+        }
+    }
+
+    pub fn state(&self) -> BehaviorState {
+        self.state
+    }
+
+    /// Advances the state machine by one control tick. Returns `true` while
+    /// a startup/idle sequence owns the keyframe slot, so the caller can
+    /// skip handing the policy's own action to the actuators this tick.
+    pub fn tick(&mut self) -> bool {
+        match self.state {
+            BehaviorState::Startup => self.tick_startup(),
+            BehaviorState::Active => {
+                self.tick_active();
+                false
+            }
+            BehaviorState::Idle => self.tick_idle(),
+        }
+    }
+
+    fn tick_startup(&mut self) -> bool {
+        let Some(step) = self.config.startup.get(self.startup_index) else {
+            self.state = BehaviorState::Active;
+            self.last_active = Instant::now();
+            return false;
+        };
+
+        keyboard::set_command_slot(KEYFRAME_SLOT, step.keyframe_index);
+        if self.step_started.elapsed() >= step.duration() {
+            self.startup_index += 1;
+            self.step_started = Instant::now();
+        }
+        true
+    }
+
+    fn tick_active(&mut self) {
+        if is_locomoting() {
+            self.last_active = Instant::now();
+        } else if self.last_active.elapsed() >= self.config.idle_timeout() {
+            self.state = BehaviorState::Idle;
+        }
+    }
+
+    fn tick_idle(&mut self) -> bool {
+        if is_locomoting() {
+            self.state = BehaviorState::Active;
+            self.last_active = Instant::now();
+            return false;
+        }
+
+        keyboard::set_command_slot(KEYFRAME_SLOT, self.config.idle_keyframe_index);
+        true
+    }
+}
+
+fn is_locomoting() -> bool {
+    let commands = keyboard::get_commands();
+    LOCOMOTION_SLOTS
+        .iter()
+        .any(|&slot| commands[slot].abs() > f32::EPSILON)
+}
@@ -33,6 +33,13 @@ struct Args {
     /// Enable keyboard commands
     #[arg(long, default_value = "false")]
     keyboard_commands: bool,
+    /// Listen for networked teleop commands on this address (e.g. 0.0.0.0:9000),
+    /// instead of or alongside `--keyboard-commands`
+    #[arg(long)]
+    teleop_listen: Option<std::net::SocketAddr>,
+    /// How long to wait for a teleop frame before zeroing commands
+    #[arg(long, default_value_t = 500)]
+    teleop_timeout_ms: u64,
     /// File logging
     #[arg(long, default_value = "false")]
     file_logging: bool,
@@ -42,6 +49,23 @@ struct Args {
     /// JSON logging
     #[arg(long)]
     json_logging: Option<String>,  // Path to JSON log file
+    /// Path to a TOML/JSON file with actuator gains, torque limits, and home
+    /// pose; falls back to the compiled defaults if omitted. Reload live by
+    /// sending SIGHUP to the process.
+    #[arg(long)]
+    config: Option<std::path::PathBuf>,
+    /// Record every control tick's command vector to this file
+    #[arg(long)]
+    record_commands: Option<std::path::PathBuf>,
+    /// Replay a previously recorded command log instead of live keyboard input
+    #[arg(long)]
+    replay_commands: Option<std::path::PathBuf>,
+    /// Loop the replayed command log instead of stopping at the end
+    #[arg(long, default_value = "false")]
+    replay_loop: bool,
+    /// Playback-rate multiplier for `--replay-commands`, composes with `--slowdown-factor`
+    #[arg(long, default_value_t = 1.0)]
+    replay_rate: f32,
 }
 
 #[tokio::main]
@@ -56,15 +80,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let model_path = Path::new(&args.model_path);
 
+    let gain_table = match &args.config {
+        Some(path) => kinfer_kbot::config::load_gain_table(path)?,
+        None => kinfer_kbot::config::GainTable::from_defaults(),
+    };
+    let gain_store = Arc::new(kinfer_kbot::config::GainStore::new(
+        gain_table,
+        args.config.clone(),
+    ));
+
     // Just prepare the keyboard info (but don't start anything yet)
     if args.keyboard_commands {
         keyboard::prepare_keyboard_listener().await?;
     }
 
-    let model_provider =
-        Arc::new(KBotProvider::new(args.torque_enabled, args.torque_scale, args.go_to_zero, args.json_logging).await?);
+    if let Some(addr) = args.teleop_listen {
+        let timeout = std::time::Duration::from_millis(args.teleop_timeout_ms);
+        tokio::spawn(async move {
+            if let Err(e) = kinfer_kbot::teleop::start_teleop_listener(addr, timeout).await {
+                tracing::error!("Teleop listener failed: {}", e);
+            }
+        });
+    }
+
+    // When a config file is given, build the actuator list, gains, and
+    // joint-name mapping from it instead of the compiled constants.
+    let model_provider = Arc::new(if args.config.is_some() {
+        KBotProvider::from_config(&gain_store.get(), args.torque_enabled, args.torque_scale).await?
+    } else {
+        KBotProvider::new(args.torque_enabled, args.torque_scale).await?
+    });
     let model_runner = ModelRunner::new(model_path, model_provider.clone()).await?;
 
+    // Wired up after `model_provider` exists so a SIGHUP reload can push the
+    // changed gains straight to the actuators, not just swap the in-memory
+    // table.
+    if args.config.is_some() {
+        let model_provider_for_reload = model_provider.clone();
+        kinfer_kbot::config::spawn_sighup_reload(gain_store.clone(), move |table| {
+            let model_provider = model_provider_for_reload.clone();
+            async move {
+                if let Err(e) = model_provider.apply_gain_table(&table).await {
+                    tracing::error!("Failed to apply reloaded gain table: {}", e);
+                }
+            }
+        })?;
+    }
+
     // Pass the keyboard_enabled flag to the runtime
     let mut model_runtime = ModelRuntime::new(
         model_provider,
@@ -75,6 +137,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     model_runtime.set_slowdown_factor(args.slowdown_factor);
     model_runtime.set_magnitude_factor(args.magnitude_factor);
 
+    if let Some(path) = args.record_commands {
+        model_runtime.enable_command_recording(path)?;
+    }
+    if let Some(path) = args.replay_commands {
+        model_runtime.enable_command_replay(path, args.replay_rate, args.replay_loop)?;
+    }
+    model_runtime.set_behavior_config(gain_store.get().behavior.clone());
+
     model_runtime.start()?;
 
     // Wait for either Ctrl-C signal OR keyboard ESC signal
@@ -0,0 +1,327 @@
+//! Runtime-loaded actuator gains, torque/velocity/current limits, joint-name
+//! mapping, bus wiring, and home pose.
+//!
+//! `constants.rs` hardcodes `ACTUATOR_NAME_TO_ID`, `ACTUATOR_KP_KD`, and
+//! `HOME_POSITION`, which forces a recompile for every tuning pass or
+//! hardware variant. This module loads the same tables (plus CAN/IMU bus
+//! settings) from a TOML or JSON file at startup, falling back to the
+//! compiled defaults when no file is given, and supports hot reloading the
+//! active table (e.g. on SIGHUP) so an operator can retune kp/kd live
+//! without restarting the model runtime. `KBotProvider::from_config` builds
+//! the actuator list and joint-name lookup straight from a `GainTable`
+//! instead of the compiled constants.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::constants::{ACTUATOR_KP_KD, ACTUATOR_NAME_TO_ID, HOME_POSITION};
+
+/// One step of the startup keyframe ramp: hold `keyframe_index` for
+/// `duration` before advancing to the next step.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartupStep {
+    pub keyframe_index: f32,
+    pub duration_ms: u64,
+}
+
+impl StartupStep {
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+/// Declarative startup/idle keyframe behavior, loaded from the same config
+/// file as the actuator gains so operators can customize safe startup/idle
+/// poses without code changes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BehaviorConfig {
+    #[serde(default)]
+    pub startup: Vec<StartupStep>,
+    #[serde(default = "default_idle_timeout_ms")]
+    pub idle_timeout_ms: u64,
+    #[serde(default)]
+    pub idle_keyframe_index: f32,
+}
+
+fn default_idle_timeout_ms() -> u64 {
+    30_000
+}
+
+impl BehaviorConfig {
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_millis(self.idle_timeout_ms)
+    }
+
+    /// No startup ramp and an idle timeout long enough to never trigger in
+    /// practice, used when the config file omits a `[behavior]` section.
+    pub fn disabled() -> Self {
+        Self {
+            startup: Vec::new(),
+            idle_timeout_ms: u64::MAX,
+            idle_keyframe_index: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActuatorGainEntry {
+    pub id: u32,
+    pub name: String,
+    pub kp: f32,
+    pub kd: f32,
+    pub max_torque: f32,
+    /// When omitted, `configure_actuator` leaves the transport's own default
+    /// limit in place, and `Actuator::kbot_actuators_from_gain_entries` uses
+    /// 10 deg/s for the actuator configuration.
+    #[serde(default)]
+    pub max_velocity: Option<f32>,
+    /// Falls back to the transport's own default limit when omitted.
+    #[serde(default)]
+    pub max_current: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HomePositionEntry {
+    pub id: u32,
+    pub position: f32,
+}
+
+/// CAN bus interfaces and IMU device/baud settings, in place of the ports
+/// hardcoded into `KBotProvider::new`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BusConfig {
+    #[serde(default = "default_can_ports")]
+    pub can_ports: Vec<String>,
+    #[serde(default = "default_imu_devices")]
+    pub imu_devices: Vec<String>,
+    #[serde(default = "default_imu_baud")]
+    pub imu_baud: u32,
+}
+
+fn default_can_ports() -> Vec<String> {
+    ["can0", "can1", "can2", "can3", "can4"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_imu_devices() -> Vec<String> {
+    ["/dev/ttyUSB0", "/dev/ttyCH341USB0"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_imu_baud() -> u32 {
+    230400
+}
+
+impl Default for BusConfig {
+    fn default() -> Self {
+        Self {
+            can_ports: default_can_ports(),
+            imu_devices: default_imu_devices(),
+            imu_baud: default_imu_baud(),
+        }
+    }
+}
+
+/// Raw, on-disk shape of the config file (TOML or JSON, same schema).
+#[derive(Debug, Clone, Deserialize)]
+struct RawGainConfig {
+    actuators: Vec<ActuatorGainEntry>,
+    home_position: Vec<HomePositionEntry>,
+    #[serde(default)]
+    behavior: Option<BehaviorConfig>,
+    #[serde(default)]
+    bus: BusConfig,
+}
+
+/// Validated, in-memory gain table consumed by the rest of the crate.
+#[derive(Debug, Clone)]
+pub struct GainTable {
+    pub behavior: BehaviorConfig,
+    pub actuators: Vec<ActuatorGainEntry>,
+    pub home_position: Vec<HomePositionEntry>,
+    pub bus: BusConfig,
+}
+
+impl GainTable {
+    /// Builds the table from the compiled-in constants, for use when no
+    /// config file is given or as the seed for validation.
+    pub fn from_defaults() -> Self {
+        let actuators = ACTUATOR_NAME_TO_ID
+            .iter()
+            .map(|(name, id)| {
+                let (_, kp, kd, max_torque) = ACTUATOR_KP_KD
+                    .iter()
+                    .find(|(row_id, _, _, _)| *row_id == *id as usize)
+                    .copied()
+                    .unwrap_or((*id as usize, 0.0, 0.0, 0.0));
+                ActuatorGainEntry {
+                    id: *id,
+                    name: name.to_string(),
+                    kp,
+                    kd,
+                    max_torque,
+                    max_velocity: None,
+                    max_current: None,
+                }
+            })
+            .collect();
+
+        let home_position = HOME_POSITION
+            .iter()
+            .map(|(id, position)| HomePositionEntry {
+                id: *id as u32,
+                position: *position,
+            })
+            .collect();
+
+        Self {
+            actuators,
+            home_position,
+            behavior: BehaviorConfig::disabled(),
+            bus: BusConfig::default(),
+        }
+    }
+
+    fn from_raw(raw: RawGainConfig) -> Self {
+        Self {
+            actuators: raw.actuators,
+            home_position: raw.home_position,
+            behavior: raw.behavior.unwrap_or_else(BehaviorConfig::disabled),
+            bus: raw.bus,
+        }
+    }
+
+    /// Joint name -> actuator ID map driven by this table's `actuators`
+    /// entries, in place of the compiled `ACTUATOR_NAME_TO_ID` constant.
+    pub fn actuator_name_to_id(&self, name: &str) -> Option<u32> {
+        self.actuators
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.id)
+    }
+
+    /// Every actuator must have a kp/kd/tau entry and a home-position entry;
+    /// a config missing either is rejected as a whole rather than applied
+    /// partially.
+    pub fn validate(&self) -> Result<()> {
+        let gain_ids: HashSet<u32> = self.actuators.iter().map(|a| a.id).collect();
+        let home_ids: HashSet<u32> = self.home_position.iter().map(|h| h.id).collect();
+
+        let missing_home: Vec<u32> = gain_ids.difference(&home_ids).copied().collect();
+        if !missing_home.is_empty() {
+            return Err(eyre!(
+                "actuators missing a home_position entry: {:?}",
+                missing_home
+            ));
+        }
+
+        let missing_gains: Vec<u32> = home_ids.difference(&gain_ids).copied().collect();
+        if !missing_gains.is_empty() {
+            return Err(eyre!(
+                "home_position entries missing a gain/torque entry: {:?}",
+                missing_gains
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_gain_config(path: &Path, contents: &str) -> Result<RawGainConfig> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(serde_json::from_str(contents)?),
+        _ => Ok(toml::from_str(contents)?),
+    }
+}
+
+/// Loads and validates a gain table from `path`, falling back to the compiled
+/// defaults (with a warning) if the path doesn't exist.
+pub fn load_gain_table(path: &Path) -> Result<GainTable> {
+    if !path.exists() {
+        warn!(
+            "Config file {:?} not found, falling back to compiled defaults",
+            path
+        );
+        return Ok(GainTable::from_defaults());
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    let table = GainTable::from_raw(parse_gain_config(path, &contents)?);
+    table.validate()?;
+    info!("Loaded actuator config from {:?}", path);
+    Ok(table)
+}
+
+/// Holds the active `GainTable` behind an atomically-swappable `Arc`, so
+/// readers never observe a partially-applied reload.
+pub struct GainStore {
+    active: RwLock<Arc<GainTable>>,
+    path: Option<PathBuf>,
+}
+
+impl GainStore {
+    pub fn new(initial: GainTable, path: Option<PathBuf>) -> Self {
+        Self {
+            active: RwLock::new(Arc::new(initial)),
+            path,
+        }
+    }
+
+    pub fn get(&self) -> Arc<GainTable> {
+        self.active.read().expect("gain table lock poisoned").clone()
+    }
+
+    /// Re-parses the config file on disk and atomically swaps the active
+    /// table in if (and only if) it validates cleanly.
+    pub fn reload(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Err(eyre!("no config path to reload from"));
+        };
+        if !path.exists() {
+            return Err(eyre!("config file {:?} not found, keeping active table", path));
+        }
+        let table = load_gain_table(path)?;
+        *self.active.write().expect("gain table lock poisoned") = Arc::new(table);
+        info!("Reloaded actuator config from {:?}", path);
+        Ok(())
+    }
+}
+
+/// Spawns a task that reloads `store` whenever the process receives SIGHUP,
+/// logging and rejecting (without applying) a config that fails validation.
+/// On a successful reload, `apply` is awaited with the new table so the
+/// caller can push the changed gains down to hardware (e.g.
+/// `KBotProvider::apply_gain_table`) — `GainStore::reload` only swaps the
+/// in-memory `Arc`, it doesn't know how to reach the actuators itself.
+#[cfg(unix)]
+pub fn spawn_sighup_reload<F, Fut>(store: Arc<GainStore>, apply: F) -> Result<()>
+where
+    F: Fn(Arc<GainTable>) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sighup = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while sighup.recv().await.is_some() {
+            match store.reload() {
+                Ok(()) => {
+                    apply(store.get()).await;
+                    info!("Gain table hot-reloaded on SIGHUP");
+                }
+                Err(e) => error!("Rejected gain table reload: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
@@ -1,11 +1,35 @@
 use ::eyre::Result;
 use imu::Vector3;
 use ::imu::{HiwonderReader, ImuReader, Quaternion};
-use ::std::time::Duration;
+use ::std::time::{Duration, Instant};
+use ::tokio::sync::Mutex;
 use ::tracing::{error, info, trace};
 
+/// Default accelerometer trust gain for the Madgwick filter. Higher values
+/// correct orientation toward gravity faster but are noisier.
+const DEFAULT_MADGWICK_BETA: f32 = 0.1;
+
+/// Nominal gravity magnitude in m/s^2, used to gate the accelerometer
+/// correction: if the measured magnitude is too far from this, the robot is
+/// undergoing linear acceleration and the reading isn't a reliable gravity
+/// reference.
+const GRAVITY: f32 = 9.81;
+
+/// Accelerometer readings whose magnitude departs from `GRAVITY` by more
+/// than this (in m/s^2) are rejected for the correction step.
+const ACCEL_REJECTION_THRESHOLD: f32 = 0.3 * GRAVITY;
+
+struct FusionState {
+    quat: Quaternion,
+    last_update: Instant,
+    /// The filter can't integrate a `dt` before its first tick.
+    initialized: bool,
+}
+
 pub struct IMU {
     imu_reader: HiwonderReader,
+    fusion: Mutex<FusionState>,
+    madgwick_beta: f32,
 }
 
 pub struct IMUData {
@@ -16,14 +40,85 @@ pub struct IMUData {
     pub gyro_y: f32,
     pub gyro_z: f32,
     pub quat: Quaternion,
+    /// The device's own orientation estimate, read straight off the sensor
+    /// before the Madgwick fusion filter touches it. `quat` is what policies
+    /// should use for anything trained on the filtered orientation; this is
+    /// for callers that specifically want the raw IMU output.
+    pub raw_quat: Quaternion,
 }
 
 const EPS: f32 = 1e-6;
 
-pub fn quat_to_euler(quat: Quaternion) -> Vector3 {
+/// Number of CORDIC micro-rotations. Each iteration roughly halves the
+/// residual angle error, so after 20 iterations (i = 0..19) the result is
+/// within `atan(2^-19)` ≈ 1.9e-6 rad (sub-arcsecond) of the true value.
+const CORDIC_ITERS: usize = 20;
+
+/// `CORDIC_ALPHA[i] = atan(2^-i)`, the micro-rotation angle for iteration
+/// `i`. Precomputed since `atan` isn't available in a `const fn`.
+const CORDIC_ALPHA: [f32; CORDIC_ITERS] = [
+    0.7853981634,
+    0.4636476090,
+    0.2449786631,
+    0.1243549945,
+    0.0624188100,
+    0.0312398334,
+    0.0156237286,
+    0.0078123411,
+    0.0039062301,
+    0.0019531225,
+    0.0009765622,
+    0.0004882812,
+    0.0002441406,
+    0.0001220703,
+    0.0000610352,
+    0.0000305176,
+    0.0000152588,
+    0.0000076294,
+    0.0000038147,
+    0.0000019073,
+];
+
+/// Fixed-iteration CORDIC `atan2(y, x)` in vectoring mode: bounded,
+/// data-independent latency in place of a libm transcendental call, which
+/// matters for a hard real-time control loop. Pre-rotates by ±π to fold the
+/// `x < 0` half-plane into the algorithm's native convergence range before
+/// running `CORDIC_ITERS` micro-rotations that drive `y` toward zero while
+/// `z` accumulates the rotation angle.
+fn cordic_atan2(y: f32, x: f32) -> f32 {
+    let (mut x, mut y, mut z) = if x < 0.0 {
+        if y >= 0.0 {
+            (-x, -y, std::f32::consts::PI)
+        } else {
+            (-x, -y, -std::f32::consts::PI)
+        }
+    } else {
+        (x, y, 0.0)
+    };
+
+    for (i, alpha) in CORDIC_ALPHA.iter().enumerate() {
+        let d = if y < 0.0 { 1.0 } else { -1.0 };
+        let scale = (2.0f32).powi(-(i as i32));
+        let next_x = x - d * y * scale;
+        let next_y = y + d * x * scale;
+        z -= d * alpha;
+        x = next_x;
+        y = next_y;
+    }
+
+    z
+}
 
+/// CORDIC `asin(t)` computed as `atan2(t, sqrt(1 - t^2))`, clamping the
+/// argument to `[-1, 1]` so a normalized quaternion that is slightly out of
+/// range (floating-point noise) doesn't produce a NaN.
+fn cordic_asin(t: f32) -> f32 {
+    let t = t.clamp(-1.0, 1.0);
+    cordic_atan2(t, (1.0 - t * t).sqrt())
+}
+
+pub fn quat_to_euler(quat: Quaternion) -> Vector3 {
     let magnitude = (quat.w * quat.w + quat.x * quat.x + quat.y * quat.y + quat.z * quat.z).sqrt();
-    
 
     let normalized_quat = Quaternion {
         w: quat.w / (magnitude + EPS),
@@ -32,13 +127,19 @@ pub fn quat_to_euler(quat: Quaternion) -> Vector3 {
         z: quat.z / (magnitude + EPS),
     };
 
-    let roll = (2.0 * (normalized_quat.w * normalized_quat.x + normalized_quat.y * normalized_quat.z))
-        .atan2(1.0 - 2.0 * (normalized_quat.x * normalized_quat.x + normalized_quat.y * normalized_quat.y));
-    
-    let pitch = (2.0 * (normalized_quat.w * normalized_quat.y - normalized_quat.z * normalized_quat.x)).asin();
-    
-    let yaw = (2.0 * (normalized_quat.w * normalized_quat.z + normalized_quat.x * normalized_quat.y))
-        .atan2(1.0 - 2.0 * (normalized_quat.y * normalized_quat.y + normalized_quat.z * normalized_quat.z));
+    let roll = cordic_atan2(
+        2.0 * (normalized_quat.w * normalized_quat.x + normalized_quat.y * normalized_quat.z),
+        1.0 - 2.0 * (normalized_quat.x * normalized_quat.x + normalized_quat.y * normalized_quat.y),
+    );
+
+    let pitch = cordic_asin(
+        2.0 * (normalized_quat.w * normalized_quat.y - normalized_quat.z * normalized_quat.x),
+    );
+
+    let yaw = cordic_atan2(
+        2.0 * (normalized_quat.w * normalized_quat.z + normalized_quat.x * normalized_quat.y),
+        1.0 - 2.0 * (normalized_quat.y * normalized_quat.y + normalized_quat.z * normalized_quat.z),
+    );
 
     Vector3::new(roll, pitch, yaw)
 }
@@ -124,7 +225,21 @@ impl IMU {
         let imu_reader = imu_reader
             .ok_or_else(|| eyre::eyre!("Failed to initialize IMU on any provided interface"))?;
 
-        Ok(Self { imu_reader })
+        Ok(Self {
+            imu_reader,
+            fusion: Mutex::new(FusionState {
+                quat: Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 },
+                last_update: Instant::now(),
+                initialized: false,
+            }),
+            madgwick_beta: DEFAULT_MADGWICK_BETA,
+        })
+    }
+
+    /// Sets the accelerometer trust gain (`beta`) used by the Madgwick
+    /// fusion filter. Higher values correct drift faster but amplify noise.
+    pub fn set_madgwick_beta(&mut self, beta: f32) {
+        self.madgwick_beta = beta;
     }
 
     pub async fn get_values(&self) -> Result<IMUData> {
@@ -139,10 +254,13 @@ impl IMU {
             Some(gyro) => gyro,
             None => return Err(eyre::eyre!("Failed to read gyroscope")),
         };
-        let quat = match direct_read.quaternion {
+        let device_quat = match direct_read.quaternion {
             Some(quat) => quat,
             None => return Err(eyre::eyre!("Failed to read quaternion")),
         };
+
+        let fused_quat = self.fuse_orientation(gyro, accel, device_quat).await;
+
         trace!("imu::get_values::END uuid={}", uuid);
         Ok(IMUData {
             accel_x: accel.x,
@@ -151,7 +269,179 @@ impl IMU {
             gyro_x: gyro.x,
             gyro_y: gyro.y,
             gyro_z: gyro.z,
-            quat,
+            quat: fused_quat,
+            raw_quat: device_quat,
         })
     }
+
+    /// Fuses gyro and accel into the running orientation estimate with a
+    /// Madgwick complementary filter, correcting the drift the device's raw
+    /// quaternion accumulates over time. On the very first tick (no prior
+    /// `dt` to integrate over) the device's own quaternion seeds the filter.
+    async fn fuse_orientation(&self, gyro: Vector3, accel: Vector3, device_quat: Quaternion) -> Quaternion {
+        let mut state = self.fusion.lock().await;
+
+        if !state.initialized {
+            state.quat = device_quat;
+            state.last_update = Instant::now();
+            state.initialized = true;
+            return state.quat;
+        }
+
+        let now = Instant::now();
+        let dt = now.duration_since(state.last_update).as_secs_f32();
+        state.last_update = now;
+
+        state.quat = madgwick_update(state.quat, gyro, accel, self.madgwick_beta, dt);
+        state.quat
+    }
+}
+
+/// One Madgwick filter step: integrates the gyro-driven quaternion
+/// derivative and, unless the accelerometer reading is rejected as linear
+/// acceleration (its magnitude too far from `GRAVITY`), blends in a gradient
+/// descent correction toward the measured gravity direction.
+fn madgwick_update(q: Quaternion, gyro: Vector3, accel: Vector3, beta: f32, dt: f32) -> Quaternion {
+    // Gyro-driven derivative: q_dot_omega = 1/2 * q (x) (0, gx, gy, gz).
+    let gyro_quat = Quaternion { w: 0.0, x: gyro.x, y: gyro.y, z: gyro.z };
+    let omega_term = rotate_quat(q, gyro_quat);
+    let mut q_dot = Quaternion {
+        w: 0.5 * omega_term.w,
+        x: 0.5 * omega_term.x,
+        y: 0.5 * omega_term.y,
+        z: 0.5 * omega_term.z,
+    };
+
+    let accel_norm = (accel.x * accel.x + accel.y * accel.y + accel.z * accel.z).sqrt();
+    if accel_norm > EPS && (accel_norm - GRAVITY).abs() <= ACCEL_REJECTION_THRESHOLD {
+        let ax = accel.x / accel_norm;
+        let ay = accel.y / accel_norm;
+        let az = accel.z / accel_norm;
+
+        let (w, x, y, z) = (q.w, q.x, q.y, q.z);
+
+        // Gravity-prediction error objective f(q) and its gradient J^T f.
+        let f1 = 2.0 * (x * z - w * y) - ax;
+        let f2 = 2.0 * (w * x + y * z) - ay;
+        let f3 = 2.0 * (0.5 - x * x - y * y) - az;
+
+        let mut grad_w = -2.0 * y * f1 + 2.0 * x * f2;
+        let mut grad_x = 2.0 * z * f1 + 2.0 * w * f2 - 4.0 * x * f3;
+        let mut grad_y = -2.0 * w * f1 + 2.0 * z * f2 - 4.0 * y * f3;
+        let mut grad_z = 2.0 * x * f1 + 2.0 * y * f2;
+
+        let grad_norm = (grad_w * grad_w + grad_x * grad_x + grad_y * grad_y + grad_z * grad_z).sqrt();
+        if grad_norm > EPS {
+            grad_w /= grad_norm;
+            grad_x /= grad_norm;
+            grad_y /= grad_norm;
+            grad_z /= grad_norm;
+
+            q_dot.w -= beta * grad_w;
+            q_dot.x -= beta * grad_x;
+            q_dot.y -= beta * grad_y;
+            q_dot.z -= beta * grad_z;
+        }
+    }
+
+    let integrated = Quaternion {
+        w: q.w + q_dot.w * dt,
+        x: q.x + q_dot.x * dt,
+        y: q.y + q_dot.y * dt,
+        z: q.z + q_dot.z * dt,
+    };
+
+    let norm = (integrated.w * integrated.w
+        + integrated.x * integrated.x
+        + integrated.y * integrated.y
+        + integrated.z * integrated.z)
+        .sqrt();
+
+    Quaternion {
+        w: integrated.w / (norm + EPS),
+        x: integrated.x / (norm + EPS),
+        y: integrated.y / (norm + EPS),
+        z: integrated.z / (norm + EPS),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANGLE_TOLERANCE: f32 = 1e-4;
+
+    #[test]
+    fn cordic_atan2_matches_the_libm_reference_in_every_quadrant() {
+        let cases: [(f32, f32); 8] = [
+            (1.0, 1.0),
+            (1.0, -1.0),
+            (-1.0, 1.0),
+            (-1.0, -1.0),
+            (0.0, 1.0),
+            (1.0, 0.0),
+            (0.5, 2.0),
+            (-0.3, 0.7),
+        ];
+
+        for (y, x) in cases {
+            let expected = y.atan2(x);
+            let actual = cordic_atan2(y, x);
+            assert!(
+                (actual - expected).abs() < ANGLE_TOLERANCE,
+                "atan2({y}, {x}): expected {expected}, got {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn quat_to_euler_recovers_a_known_90_degree_yaw() {
+        // Rotation of +90 degrees about Z: x = y = 0, w = cos(45 deg),
+        // z = sin(45 deg).
+        let half_angle: f32 = std::f32::consts::FRAC_PI_4;
+        let quat = Quaternion { w: half_angle.cos(), x: 0.0, y: 0.0, z: half_angle.sin() };
+
+        let euler = quat_to_euler(quat);
+        assert!(euler.x.abs() < ANGLE_TOLERANCE, "roll: {}", euler.x);
+        assert!(euler.y.abs() < ANGLE_TOLERANCE, "pitch: {}", euler.y);
+        assert!(
+            (euler.z - std::f32::consts::FRAC_PI_2).abs() < ANGLE_TOLERANCE,
+            "yaw: {}",
+            euler.z
+        );
+    }
+
+    #[test]
+    fn madgwick_update_holds_steady_when_already_aligned_with_gravity() {
+        // Identity orientation, zero gyro, and accel already pointing
+        // exactly where the filter predicts gravity should be: the
+        // correction gradient and gyro term are both zero, so one step
+        // should leave the quaternion unchanged (up to renormalization).
+        let identity = Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+        let gyro = Vector3::new(0.0, 0.0, 0.0);
+        let accel = Vector3::new(0.0, 0.0, GRAVITY);
+
+        let updated = madgwick_update(identity, gyro, accel, DEFAULT_MADGWICK_BETA, 0.01);
+
+        assert!((updated.w - 1.0).abs() < 1e-3);
+        assert!(updated.x.abs() < 1e-3);
+        assert!(updated.y.abs() < 1e-3);
+        assert!(updated.z.abs() < 1e-3);
+    }
+
+    #[test]
+    fn madgwick_update_integrates_a_pure_yaw_rate() {
+        // No accel correction (reading rejected by gating it out entirely
+        // with a zero vector), so the filter should just integrate the
+        // gyro's z-rate over dt into a small positive yaw.
+        let identity = Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+        let gyro = Vector3::new(0.0, 0.0, 1.0);
+        let accel = Vector3::new(0.0, 0.0, 0.0);
+
+        let updated = madgwick_update(identity, gyro, accel, DEFAULT_MADGWICK_BETA, 0.01);
+        let euler = quat_to_euler(updated);
+
+        assert!(euler.z > 0.0);
+        assert!((euler.z - 0.01).abs() < 1e-3);
+    }
 }
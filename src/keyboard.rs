@@ -2,8 +2,8 @@ use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
     terminal::{disable_raw_mode, enable_raw_mode},
 };
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 // Global command state
 static COMMAND_X: AtomicU32 = AtomicU32::new(0);
@@ -16,6 +16,99 @@ static COMMAND_ROLL: AtomicU32 = AtomicU32::new(0);
 static KEYFRAME_INDEX: AtomicU32 = AtomicU32::new(0);
 static KEYBOARD_RUNNING: AtomicBool = AtomicBool::new(false);
 static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+static REPLAY_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// How long a repeatable key must be held before the first autorepeat tick
+/// fires; see `set_repeat_delay`.
+static REPEAT_DELAY_MS: AtomicU64 = AtomicU64::new(250);
+/// Spacing between autorepeat ticks after the initial delay; see
+/// `set_repeat_period`.
+static REPEAT_PERIOD_MS: AtomicU64 = AtomicU64::new(30);
+
+/// How long a repeatable key (w/a/s/d/q/e/r/f/t/g) must be held before it
+/// starts autorepeating. Surfaced through `ModelRuntime::set_repeat_delay`.
+pub fn set_repeat_delay(delay: Duration) {
+    REPEAT_DELAY_MS.store(delay.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Spacing between autorepeat ticks once a held key starts repeating.
+/// Surfaced through `ModelRuntime::set_repeat_period`.
+pub fn set_repeat_period(period: Duration) {
+    REPEAT_PERIOD_MS.store(period.as_millis() as u64, Ordering::Relaxed);
+}
+
+fn repeat_delay() -> Duration {
+    Duration::from_millis(REPEAT_DELAY_MS.load(Ordering::Relaxed))
+}
+
+fn repeat_period() -> Duration {
+    Duration::from_millis(REPEAT_PERIOD_MS.load(Ordering::Relaxed))
+}
+
+/// Movement/orientation keys that make sense to hold down; digit keys and
+/// space are one-shot selectors and never repeat.
+fn is_repeatable(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::Char('w' | 's' | 'a' | 'd' | 'q' | 'e' | 'r' | 'f' | 't' | 'g')
+    )
+}
+
+/// Why a tap-dance gesture finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapDanceReason {
+    Timeout,
+    OtherKey,
+}
+
+/// Key bound to the pause/home/e-stop tap-dance gesture: tapping it once
+/// toggles pause, twice sends the robot to its home pose, and three or more
+/// times forces an emergency stop — three behaviors on one key instead of
+/// three physical keys.
+const TAP_DANCE_TRIGGER: KeyCode = KeyCode::Char('p');
+const TAP_DANCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static HOME_REQUESTED: AtomicBool = AtomicBool::new(false);
+static ESTOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// Returns `true` at most once per request, then clears itself.
+/// `ModelRuntime`'s control loop polls this each tick to know whether to
+/// call `KBotProvider::move_to_home`.
+pub fn take_home_requested() -> bool {
+    HOME_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// Returns `true` at most once per request, then clears itself.
+/// `ModelRuntime`'s control loop polls this each tick to know whether to
+/// call `KBotProvider::emergency_stop`.
+pub fn take_estop_requested() -> bool {
+    ESTOP_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// Dispatches the action bound to a completed tap-dance gesture.
+fn complete_tap_dance(tap_count: u8, reason: TapDanceReason) {
+    tracing::debug!("Tap-dance on trigger key completed: {} tap(s), {:?}", tap_count, reason);
+    match tap_count {
+        1 => {
+            let paused = !PAUSED.load(Ordering::Relaxed);
+            PAUSED.store(paused, Ordering::Relaxed);
+            println!("{}", if paused { "Paused" } else { "Resumed" });
+        }
+        2 => HOME_REQUESTED.store(true, Ordering::Relaxed),
+        _ => ESTOP_REQUESTED.store(true, Ordering::Relaxed),
+    }
+}
+
+/// While a recorded command trajectory is being replayed, live keyboard
+/// input should drive nothing except the ESC shutdown key.
+pub fn set_replay_active(active: bool) {
+    REPLAY_ACTIVE.store(active, Ordering::Relaxed);
+}
 
 pub fn get_commands() -> [f32; 8] {
     [
@@ -30,6 +123,20 @@ pub fn get_commands() -> [f32; 8] {
     ]
 }
 
+/// Overwrites all eight command slots at once, e.g. from a networked teleop
+/// frame or a replayed trajectory, rather than one key at a time.
+pub fn set_all_commands(commands: [f32; 8]) {
+    for (index, value) in commands.into_iter().enumerate() {
+        set_command(index, value);
+    }
+}
+
+/// Overwrites a single command slot, e.g. the keyframe index driven by the
+/// startup/idle behavior state machine.
+pub fn set_command_slot(index: usize, value: f32) {
+    set_command(index, value);
+}
+
 #[inline]
 fn set_command(index: usize, value: f32) {
     let bits = value.to_bits();
@@ -52,10 +159,63 @@ pub async fn prepare_keyboard_listener() -> Result<(), Box<dyn std::error::Error
     println!("  A/D: Y velocity (left/right)");
     println!("  Q/E: Yaw rate (turn left/right)");
     println!("  Space: Reset all commands");
+    println!("  P: tap once to pause, twice for home, thrice for e-stop");
     println!("  ESC: Exit program gracefully");
     Ok(())
 }
 
+/// Applies one key-down action. Shared between the original press and every
+/// autorepeat tick it spawns, so a held key re-runs the exact same effect
+/// (accumulating yaw/roll/pitch the same way N discrete presses would).
+fn apply_key_press(code: KeyCode) {
+    match code {
+        KeyCode::Char('w') => set_command(0, 0.2),
+        KeyCode::Char('s') => set_command(0, -0.2),
+        KeyCode::Char('a') => set_command(1, 0.2),
+        KeyCode::Char('d') => set_command(1, -0.2),
+        KeyCode::Char('q') => {
+            let current_yaw = f32::from_bits(COMMAND_YAW.load(Ordering::Relaxed));
+            set_command(2, 0.1);
+            set_command(3, current_yaw + 0.1);
+        }
+        KeyCode::Char('e') => {
+            let current_yaw = f32::from_bits(COMMAND_YAW.load(Ordering::Relaxed));
+            set_command(2, -0.1);
+            set_command(3, current_yaw - 0.1);
+        }
+        KeyCode::Char('r') => {
+            let current_roll = f32::from_bits(COMMAND_ROLL.load(Ordering::Relaxed));
+            set_command(5, current_roll + 0.1);
+        }
+        KeyCode::Char('f') => {
+            let current_roll = f32::from_bits(COMMAND_ROLL.load(Ordering::Relaxed));
+            set_command(5, current_roll - 0.1);
+        }
+        KeyCode::Char('t') => {
+            let current_pitch = f32::from_bits(COMMAND_PITCH.load(Ordering::Relaxed));
+            set_command(6, current_pitch + 0.1);
+        }
+        KeyCode::Char('g') => {
+            let current_pitch = f32::from_bits(COMMAND_PITCH.load(Ordering::Relaxed));
+            set_command(6, current_pitch - 0.1);
+        }
+        KeyCode::Char('6') => set_command(7, 6.0),
+        KeyCode::Char('7') => set_command(7, 7.0),
+        KeyCode::Char('8') => set_command(7, 8.0),
+        KeyCode::Char('9') => set_command(7, 9.0),
+        KeyCode::Char('2') => {
+            COMMAND_X.store(0, Ordering::Relaxed);
+            COMMAND_Y.store(0, Ordering::Relaxed);
+            COMMAND_YAW.store(0, Ordering::Relaxed);
+            COMMAND_YAW_RATE.store(0, Ordering::Relaxed);
+            COMMAND_HEIGHT.store(0, Ordering::Relaxed);
+            COMMAND_PITCH.store(0, Ordering::Relaxed);
+            COMMAND_ROLL.store(0, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+}
+
 pub fn start_keyboard_listener_now() {
     KEYBOARD_RUNNING.store(true, Ordering::Relaxed);
 
@@ -65,84 +225,104 @@ pub fn start_keyboard_listener_now() {
             return;
         }
 
+        // The currently-held repeatable key and when its next autorepeat
+        // tick is due; `None` once it's released or superseded.
+        let mut held: Option<(KeyCode, Instant)> = None;
+        // In-progress tap-dance on `TAP_DANCE_TRIGGER`: taps so far and the
+        // deadline by which another tap must arrive to extend the dance.
+        let mut tap_dance: Option<(u8, Instant)> = None;
+
         while KEYBOARD_RUNNING.load(Ordering::Relaxed) {
-            // Block until an event is available (no polling!)
-            // This uses zero CPU when no keys are pressed
-            match event::read() {
-                Ok(Event::Key(KeyEvent { code, kind, .. })) => {
-                    // Handle ESC as graceful shutdown signal
-                    if matches!(code, KeyCode::Esc) && kind == KeyEventKind::Press {
-                        println!("\nESC pressed - requesting graceful shutdown...");
-                        SHUTDOWN_REQUESTED.store(true, Ordering::Relaxed);
-                        KEYBOARD_RUNNING.store(false, Ordering::Relaxed);
-                        break;
-                    }
+            // Poll with a timeout instead of blocking forever on `read()`,
+            // so a held key's autorepeat deadline and an in-progress
+            // tap-dance's timeout get serviced even when no new terminal
+            // event arrives.
+            let now = Instant::now();
+            let next_deadline = [held.map(|(_, t)| t), tap_dance.map(|(_, t)| t)]
+                .into_iter()
+                .flatten()
+                .min();
+            let timeout = match next_deadline {
+                Some(deadline) => deadline.saturating_duration_since(now),
+                None => Duration::from_millis(50),
+            };
 
-                    // Handle key events immediately when they occur
-                    match (kind, code) {
-                        (KeyEventKind::Press, KeyCode::Char('w')) => set_command(0, 0.2),
-                        (KeyEventKind::Press, KeyCode::Char('s')) => set_command(0, -0.2),
-                        (KeyEventKind::Press, KeyCode::Char('a')) => set_command(1, 0.2),
-                        (KeyEventKind::Press, KeyCode::Char('d')) => set_command(1, -0.2),
-                        (KeyEventKind::Press, KeyCode::Char('q')) => {
-                            let current_yaw = f32::from_bits(COMMAND_YAW.load(Ordering::Relaxed));
-                            set_command(2, 0.1);
-                            set_command(3, current_yaw + 0.1);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('e')) => {
-                            let current_yaw = f32::from_bits(COMMAND_YAW.load(Ordering::Relaxed));
-                            set_command(2, -0.1);
-                            set_command(3, current_yaw - 0.1);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('r')) => {
-                            let current_roll = f32::from_bits(COMMAND_ROLL.load(Ordering::Relaxed));
-                            set_command(5, current_roll + 0.1);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('f')) => {
-                            let current_roll = f32::from_bits(COMMAND_ROLL.load(Ordering::Relaxed));
-                            set_command(5, current_roll - 0.1);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('t')) => {
-                            let current_pitch =
-                                f32::from_bits(COMMAND_PITCH.load(Ordering::Relaxed));
-                            set_command(6, current_pitch + 0.1);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('g')) => {
-                            let current_pitch =
-                                f32::from_bits(COMMAND_PITCH.load(Ordering::Relaxed));
-                            set_command(6, current_pitch - 0.1);
+            match event::poll(timeout) {
+                Ok(true) => match event::read() {
+                    Ok(Event::Key(KeyEvent { code, kind, .. })) => {
+                        // Handle ESC as graceful shutdown signal
+                        if matches!(code, KeyCode::Esc) && kind == KeyEventKind::Press {
+                            println!("\nESC pressed - requesting graceful shutdown...");
+                            SHUTDOWN_REQUESTED.store(true, Ordering::Relaxed);
+                            KEYBOARD_RUNNING.store(false, Ordering::Relaxed);
+                            break;
                         }
-                        (KeyEventKind::Press, KeyCode::Char('6')) => {
-                            set_command(7, 6.0);
-                        }
-                        (KeyEventKind::Press, KeyCode::Char('7')) => {
-                            set_command(7, 7.0);
+
+                        // Handle key events immediately when they occur, unless a
+                        // recorded trajectory is being replayed.
+                        if REPLAY_ACTIVE.load(Ordering::Relaxed) {
+                            continue;
                         }
-                        (KeyEventKind::Press, KeyCode::Char('8')) => {
-                            set_command(7, 8.0);
+
+                        match kind {
+                            KeyEventKind::Press if code == TAP_DANCE_TRIGGER => {
+                                let tap_count = match tap_dance {
+                                    Some((count, _)) => count.saturating_add(1),
+                                    None => 1,
+                                };
+                                tap_dance = Some((tap_count, Instant::now() + TAP_DANCE_TIMEOUT));
+                            }
+                            KeyEventKind::Press => {
+                                // Any other key arriving completes an
+                                // in-progress tap-dance early.
+                                if let Some((tap_count, _)) = tap_dance.take() {
+                                    complete_tap_dance(tap_count, TapDanceReason::OtherKey);
+                                }
+
+                                // A different non-modifier key arriving cancels
+                                // whatever was previously armed.
+                                if held.map(|(held_code, _)| held_code) != Some(code) {
+                                    held = None;
+                                }
+                                apply_key_press(code);
+                                if is_repeatable(code) {
+                                    held = Some((code, Instant::now() + repeat_delay()));
+                                }
+                            }
+                            KeyEventKind::Release => {
+                                if held.map(|(held_code, _)| held_code) == Some(code) {
+                                    held = None;
+                                }
+                                match code {
+                                    KeyCode::Char('w' | 's') => set_command(0, 0.0),
+                                    KeyCode::Char('a' | 'd') => set_command(1, 0.0),
+                                    KeyCode::Char('q' | 'e') => set_command(2, 0.0),
+                                    _ => {}
+                                }
+                            }
+                            _ => {}
                         }
-                        (KeyEventKind::Press, KeyCode::Char('9')) => {
-                            set_command(7, 9.0);
+                    }
+                    Ok(_) => {}
+                    Err(_) => break,
+                },
+                Ok(false) => {
+                    // Timed out: service whichever deadlines have arrived.
+                    let now = Instant::now();
+                    if let Some((code, next_fire)) = held {
+                        if now >= next_fire && !REPLAY_ACTIVE.load(Ordering::Relaxed) {
+                            apply_key_press(code);
+                            held = Some((code, now + repeat_period()));
                         }
-                        (KeyEventKind::Press, KeyCode::Char('2')) => {
-                            COMMAND_X.store(0, Ordering::Relaxed);
-                            COMMAND_Y.store(0, Ordering::Relaxed);
-                            COMMAND_YAW.store(0, Ordering::Relaxed);
-                            COMMAND_YAW_RATE.store(0, Ordering::Relaxed);
-                            COMMAND_HEIGHT.store(0, Ordering::Relaxed);
-                            COMMAND_PITCH.store(0, Ordering::Relaxed);
-                            COMMAND_ROLL.store(0, Ordering::Relaxed);
+                    }
+                    if let Some((tap_count, deadline)) = tap_dance {
+                        if now >= deadline {
+                            complete_tap_dance(tap_count, TapDanceReason::Timeout);
+                            tap_dance = None;
                         }
-                        (KeyEventKind::Release, KeyCode::Char('w' | 's')) => set_command(0, 0.0),
-                        (KeyEventKind::Release, KeyCode::Char('a' | 'd')) => set_command(1, 0.0),
-                        (KeyEventKind::Release, KeyCode::Char('q' | 'e')) => set_command(2, 0.0),
-                        _ => {}
                     }
                 }
-                Ok(_) => {}
-                Err(_) => {
-                    break;
-                }
+                Err(_) => break,
             }
         }
 
@@ -0,0 +1,260 @@
+//! Forward kinematics: builds a serial chain per limb from segment
+//! transforms (a joint axis plus a fixed frame offset, KDL-style) and a
+//! recursive forward-position solver that composes them into a tip pose.
+//! Chain definitions are data-driven (loaded from the robot's link/joint
+//! table), so arm and leg tips can be added without code changes.
+//!
+//! Note: `kinfer::InputType` doesn't yet have `FeetPositions` /
+//! `CenterOfMass` variants, so this isn't wired into
+//! `KBotProvider::get_inputs` — once upstream adds them, add match arms
+//! there that call `KBotProvider::get_feet_positions` /
+//! `get_center_of_mass`, following the existing `JointAngles` pattern.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use eyre::Result;
+use serde::Deserialize;
+
+/// A 4x4 homogeneous transform, row-major.
+pub type Mat4 = [[f64; 4]; 4];
+
+fn identity() -> Mat4 {
+    let mut m = [[0.0; 4]; 4];
+    for i in 0..4 {
+        m[i][i] = 1.0;
+    }
+    m
+}
+
+fn mat_mul(a: &Mat4, b: &Mat4) -> Mat4 {
+    let mut out = [[0.0; 4]; 4];
+    for row in 0..4 {
+        for col in 0..4 {
+            out[row][col] = (0..4).map(|k| a[row][k] * b[k][col]).sum();
+        }
+    }
+    out
+}
+
+fn translation(t: [f64; 3]) -> Mat4 {
+    let mut m = identity();
+    m[0][3] = t[0];
+    m[1][3] = t[1];
+    m[2][3] = t[2];
+    m
+}
+
+/// Rotation by `angle` radians about a (not necessarily normalized) `axis`,
+/// via Rodrigues' rotation formula.
+fn rotation_about_axis(axis: [f64; 3], angle: f64) -> Mat4 {
+    let norm = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+    if norm < 1e-9 {
+        return identity();
+    }
+    let (x, y, z) = (axis[0] / norm, axis[1] / norm, axis[2] / norm);
+    let (s, c) = angle.sin_cos();
+    let t = 1.0 - c;
+
+    let mut m = identity();
+    m[0][0] = t * x * x + c;
+    m[0][1] = t * x * y - s * z;
+    m[0][2] = t * x * z + s * y;
+    m[1][0] = t * x * y + s * z;
+    m[1][1] = t * y * y + c;
+    m[1][2] = t * y * z - s * x;
+    m[2][0] = t * x * z - s * y;
+    m[2][1] = t * y * z + s * x;
+    m[2][2] = t * z * z + c;
+    m
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Segment {
+    /// Joint whose angle drives this segment's rotation; must match a
+    /// `joint_names` entry from the model's metadata.
+    pub joint_name: String,
+    /// Joint rotation axis, in the parent segment's frame.
+    pub axis: [f64; 3],
+    /// Fixed frame offset applied after the joint rotation.
+    pub offset: [f64; 3],
+    /// Link mass, used for the center-of-mass calculation.
+    #[serde(default)]
+    pub mass: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Chain {
+    /// Name of the tip frame this chain resolves, e.g. `"left_foot"`.
+    pub name: String,
+    pub segments: Vec<Segment>,
+}
+
+impl Chain {
+    /// Forward-position solve (`JntToCart`): composes each segment's joint
+    /// rotation with its fixed frame offset, in order, as
+    /// `T = Π (R_joint(q_i) · T_offset_i)`.
+    fn segment_transforms(&self, joint_angles: &HashMap<String, f64>) -> Vec<Mat4> {
+        let mut t = identity();
+        let mut transforms = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            let q = joint_angles.get(&segment.joint_name).copied().unwrap_or(0.0);
+            let segment_transform = mat_mul(&rotation_about_axis(segment.axis, q), &translation(segment.offset));
+            t = mat_mul(&t, &segment_transform);
+            transforms.push(t);
+        }
+        transforms
+    }
+
+    /// The tip frame's full 4x4 pose.
+    pub fn tip_pose(&self, joint_angles: &HashMap<String, f64>) -> Mat4 {
+        self.segment_transforms(joint_angles)
+            .last()
+            .copied()
+            .unwrap_or_else(identity)
+    }
+
+    /// The tip frame's translation, i.e. the Cartesian foot/end-effector
+    /// position.
+    pub fn tip_translation(&self, joint_angles: &HashMap<String, f64>) -> [f64; 3] {
+        let t = self.tip_pose(joint_angles);
+        [t[0][3], t[1][3], t[2][3]]
+    }
+
+    /// Each link's frame origin and mass, for the mass-weighted center of
+    /// mass.
+    fn link_origins(&self, joint_angles: &HashMap<String, f64>) -> Vec<([f64; 3], f64)> {
+        self.segment_transforms(joint_angles)
+            .iter()
+            .zip(&self.segments)
+            .map(|(t, segment)| ([t[0][3], t[1][3], t[2][3]], segment.mass))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RawKinematicsConfig {
+    chains: Vec<Chain>,
+}
+
+/// A robot's forward-kinematics chains, e.g. one per foot and hand tip.
+pub struct KinematicsModel {
+    chains: Vec<Chain>,
+}
+
+impl KinematicsModel {
+    /// Loads chain definitions from the robot's link/joint table (TOML) so
+    /// new tip frames can be added without code changes.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let raw: RawKinematicsConfig = toml::from_str(&contents)?;
+        Ok(Self { chains: raw.chains })
+    }
+
+    /// Tip translation for every configured chain, keyed by chain name.
+    pub fn tip_positions(&self, joint_angles: &HashMap<String, f64>) -> HashMap<String, [f64; 3]> {
+        self.chains
+            .iter()
+            .map(|chain| (chain.name.clone(), chain.tip_translation(joint_angles)))
+            .collect()
+    }
+
+    /// Mass-weighted average of every configured chain's per-link frame
+    /// origins.
+    pub fn center_of_mass(&self, joint_angles: &HashMap<String, f64>) -> [f64; 3] {
+        let mut weighted = [0.0f64; 3];
+        let mut total_mass = 0.0;
+
+        for chain in &self.chains {
+            for (origin, mass) in chain.link_origins(joint_angles) {
+                for axis in 0..3 {
+                    weighted[axis] += origin[axis] * mass;
+                }
+                total_mass += mass;
+            }
+        }
+
+        if total_mass > 0.0 {
+            for axis in weighted.iter_mut() {
+                *axis /= total_mass;
+            }
+        }
+        weighted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_link_chain() -> Chain {
+        Chain {
+            name: "test_chain".to_string(),
+            segments: vec![
+                Segment {
+                    joint_name: "joint_0".to_string(),
+                    axis: [0.0, 0.0, 1.0],
+                    offset: [1.0, 0.0, 0.0],
+                    mass: 1.0,
+                },
+                Segment {
+                    joint_name: "joint_1".to_string(),
+                    axis: [0.0, 0.0, 1.0],
+                    offset: [1.0, 0.0, 0.0],
+                    mass: 1.0,
+                },
+            ],
+        }
+    }
+
+    fn assert_close(actual: [f64; 3], expected: [f64; 3]) {
+        for axis in 0..3 {
+            assert!(
+                (actual[axis] - expected[axis]).abs() < 1e-9,
+                "axis {}: expected {:?}, got {:?}",
+                axis,
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn tip_translation_at_zero_angle_is_the_summed_offsets() {
+        let chain = two_link_chain();
+        let angles = HashMap::from([("joint_0".to_string(), 0.0), ("joint_1".to_string(), 0.0)]);
+        assert_close(chain.tip_translation(&angles), [2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn tip_translation_rotates_downstream_links_with_the_base_joint() {
+        // Rotating the base joint 90 degrees about Z should carry both the
+        // first link's tip and the second link's offset from pointing along
+        // +X to pointing along +Y, so the chain ends up twice as far along
+        // +Y as a single link, not split between axes.
+        let chain = two_link_chain();
+        let angles = HashMap::from([
+            ("joint_0".to_string(), std::f64::consts::FRAC_PI_2),
+            ("joint_1".to_string(), 0.0),
+        ]);
+        assert_close(chain.tip_translation(&angles), [0.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn tip_translation_falls_back_to_zero_for_a_missing_joint_angle() {
+        let chain = two_link_chain();
+        let angles = HashMap::new();
+        assert_close(chain.tip_translation(&angles), [2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn center_of_mass_is_the_mass_weighted_average_of_link_origins() {
+        // Link origins at [1, 0, 0] and [2, 0, 0], equal unit masses, so the
+        // center of mass sits at their midpoint.
+        let model = KinematicsModel {
+            chains: vec![two_link_chain()],
+        };
+        let angles = HashMap::from([("joint_0".to_string(), 0.0), ("joint_1".to_string(), 0.0)]);
+        assert_close(model.center_of_mass(&angles), [1.5, 0.0, 0.0]);
+    }
+}
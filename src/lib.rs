@@ -9,11 +9,19 @@ use tracing_subscriber::{
 };
 
 pub mod actuators;
+pub mod behavior;
+pub mod clock;
+pub mod config;
 pub mod constants;
 pub mod imu;
 pub mod keyboard;
+pub mod kinematics;
+pub mod playback;
 pub mod provider;
 pub mod runtime;
+pub mod scheduler;
+pub mod state_estimator;
+pub mod teleop;
 
 pub fn initialize_logging() {
     let subscriber = FmtSubscriber::builder()
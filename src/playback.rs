@@ -0,0 +1,194 @@
+//! Deterministic recording and replay of the command vector consumed by
+//! `keyboard::get_commands()`, modeled on distributed-DMA sequence playback:
+//! a captured teleop or keyboard session reproduces bit-for-bit on the robot
+//! or in a dry run.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use eyre::{eyre, Result};
+
+const NUM_COMMAND_SLOTS: usize = 8;
+/// One recorded tick: an 8-byte microsecond timestamp followed by eight
+/// little-endian `f32` command values.
+const ENTRY_BYTES: usize = 8 + NUM_COMMAND_SLOTS * 4;
+
+/// Appends every control tick's command vector, with a microsecond
+/// timestamp relative to when recording started, to a compact binary log.
+pub struct CommandRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl CommandRecorder {
+    pub fn new(path: &Path) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            start: Instant::now(),
+        })
+    }
+
+    /// Rebases the recorded timestamps to start from now. `new` stamps
+    /// `start` at CLI-arg-setup time, well before `ModelRuntime::start`'s
+    /// operator-paced "Press enter to Home"/"Press enter to start" prompts
+    /// and startup countdown, so without this the first recorded tick's
+    /// timestamp would include however long the operator took to answer
+    /// those prompts. Call this right before the control loop begins.
+    pub fn reset_start(&mut self) {
+        self.start = Instant::now();
+    }
+
+    pub fn record_tick(&mut self, commands: [f32; NUM_COMMAND_SLOTS]) -> Result<()> {
+        let micros = self.start.elapsed().as_micros() as u64;
+        self.writer.write_all(&micros.to_le_bytes())?;
+        for value in commands {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Frame {
+    micros: u64,
+    commands: [f32; NUM_COMMAND_SLOTS],
+}
+
+/// Replays a previously recorded command log, advancing by wall-clock time
+/// (scaled by a playback-rate multiplier) rather than by tick count, so it
+/// composes with `ModelRuntime`'s `slowdown_factor`.
+pub struct CommandReplayer {
+    frames: Vec<Frame>,
+    start: std::sync::Mutex<Instant>,
+    rate: f32,
+    looping: bool,
+    finished: AtomicBool,
+}
+
+impl CommandReplayer {
+    pub fn load(path: &Path, rate: f32, looping: bool) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut frames = Vec::new();
+        let mut buf = [0u8; ENTRY_BYTES];
+        loop {
+            match reader.read_exact(&mut buf) {
+                Ok(()) => {
+                    let micros = u64::from_le_bytes(buf[0..8].try_into().expect("8 bytes"));
+                    let mut commands = [0.0f32; NUM_COMMAND_SLOTS];
+                    for (slot, chunk) in commands.iter_mut().zip(buf[8..].chunks_exact(4)) {
+                        *slot = f32::from_le_bytes(chunk.try_into().expect("4 bytes"));
+                    }
+                    frames.push(Frame { micros, commands });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if frames.is_empty() {
+            return Err(eyre!("replay log {:?} is empty", path));
+        }
+
+        Ok(Self {
+            frames,
+            start: std::sync::Mutex::new(Instant::now()),
+            rate,
+            looping,
+            finished: AtomicBool::new(false),
+        })
+    }
+
+    /// Rebases the replay's wall-clock offset to start from now; see
+    /// `CommandRecorder::reset_start` for why `load`'s timestamp is too
+    /// early to use as-is. Call this right before the control loop begins.
+    pub fn reset_start(&self) {
+        *self.start.lock().expect("replayer start lock poisoned") = Instant::now();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// Returns the command vector held at the current wall-clock offset into
+    /// the recording, scaled by the playback-rate multiplier.
+    pub fn commands(&self) -> [f32; NUM_COMMAND_SLOTS] {
+        let duration = self.frames.last().expect("loaded frames are non-empty").micros;
+        let start = *self.start.lock().expect("replayer start lock poisoned");
+        let elapsed_micros = (start.elapsed().as_micros() as f32 * self.rate) as u64;
+
+        let offset = if duration == 0 {
+            0
+        } else if self.looping {
+            elapsed_micros % duration
+        } else if elapsed_micros >= duration {
+            self.finished.store(true, Ordering::Relaxed);
+            duration
+        } else {
+            elapsed_micros
+        };
+
+        // Hold the last frame at or before `offset` (step interpolation).
+        match self.frames.binary_search_by_key(&offset, |f| f.micros) {
+            Ok(idx) => self.frames[idx].commands,
+            Err(0) => self.frames[0].commands,
+            Err(idx) => self.frames[idx - 1].commands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("kinfer_kbot_playback_test_{}_{}.bin", std::process::id(), name))
+    }
+
+    #[test]
+    fn record_then_replay_round_trips_the_command_frames() {
+        let path = temp_path("round_trip");
+        let written = [
+            [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.5, -0.25, 0.0, 0.0, 0.0, 1.0],
+        ];
+
+        {
+            let mut recorder = CommandRecorder::new(&path).expect("create recorder");
+            for commands in &written {
+                recorder.record_tick(*commands).expect("record tick");
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+            recorder.flush().expect("flush recorder");
+        }
+
+        let replayer = CommandReplayer::load(&path, 1.0, false).expect("load replayer");
+        assert_eq!(replayer.frames.len(), written.len());
+        for (frame, expected) in replayer.frames.iter().zip(written.iter()) {
+            assert_eq!(frame.commands, *expected);
+        }
+        assert!(replayer.frames[0].micros < replayer.frames[1].micros);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn load_rejects_an_empty_log() {
+        let path = temp_path("empty");
+        CommandRecorder::new(&path)
+            .expect("create recorder")
+            .flush()
+            .expect("flush recorder");
+
+        assert!(CommandReplayer::load(&path, 1.0, false).is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
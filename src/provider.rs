@@ -3,16 +3,53 @@ use ::imu::{Quaternion, Vector3};
 use ::kinfer::{InputType, ModelError, ModelMetadata, ModelProvider};
 use ::ndarray::{Array, IxDyn};
 use ::std::collections::HashMap;
+use ::std::sync::{Arc, Mutex};
 use ::std::time::{Duration, Instant};
 
 use crate::actuators::{Actuator, ActuatorCommand, ActuatorState, ConfigureRequest};
+use crate::config::GainTable;
 use crate::constants::{ACTUATOR_KP_KD, ACTUATOR_NAME_TO_ID, HOME_POSITION};
 use crate::imu::IMU;
+use crate::kinematics::KinematicsModel;
+use crate::state_estimator::StateEstimator;
 
 pub struct KBotProvider {
     actuators: Actuator,
     imu: IMU,
     start_time: Instant,
+    /// Yaw captured from the first IMU reading, so policies trained with a
+    /// fixed reference heading see a stable frame rather than a per-step
+    /// value. Populated lazily on first read.
+    initial_heading: std::sync::OnceLock<f32>,
+    /// Latest setpoint pushed via `set_command`, e.g. from a gamepad or
+    /// network task. Defaults to all zeros (stand still) until first set.
+    command_source: Arc<Mutex<Vec<f32>>>,
+    /// Exponentially smoothed version of `command_source`, to avoid step
+    /// discontinuities into the policy.
+    smoothed_command: Mutex<Vec<f32>>,
+    /// At or below this per-slot delta, smoothing is skipped and the slot
+    /// takes the setpoint directly, rather than chasing it asymptotically.
+    command_deadband: f32,
+    /// Exponential smoothing factor in `(0, 1]`; higher tracks the setpoint
+    /// faster, lower damps discontinuities more.
+    command_smoothing_alpha: f32,
+    /// `num_commands` observed on the first `Command` input request, used to
+    /// validate the length of `set_command` pushes.
+    expected_num_commands: std::sync::OnceLock<usize>,
+    /// Forward-kinematics chains for foot/end-effector poses and
+    /// center-of-mass, loaded via `load_kinematics`.
+    kinematics: Option<KinematicsModel>,
+    /// Fused base orientation/angular-velocity estimate and per-joint
+    /// friction model, updated each tick from the same hardware read as
+    /// `get_inputs`. Paired with the timestamp of its last update.
+    state_estimator: tokio::sync::Mutex<(StateEstimator, Instant)>,
+    /// Joint name -> actuator ID mapping. Defaults to `ACTUATOR_NAME_TO_ID`;
+    /// overridden by `from_config` so a config file's joint set doesn't
+    /// require a recompile.
+    joint_name_to_id: Vec<(String, u32)>,
+    /// Per-actuator resting position used by `move_to_home`. Defaults to
+    /// `HOME_POSITION`; overridden by `from_config`.
+    home_position: Vec<(u32, f32)>,
 }
 
 impl KBotProvider {
@@ -64,16 +101,178 @@ impl KBotProvider {
             actuators,
             imu,
             start_time: Instant::now(),
+            initial_heading: std::sync::OnceLock::new(),
+            command_source: Arc::new(Mutex::new(Vec::new())),
+            smoothed_command: Mutex::new(Vec::new()),
+            command_deadband: 0.01,
+            command_smoothing_alpha: 0.3,
+            expected_num_commands: std::sync::OnceLock::new(),
+            kinematics: None,
+            state_estimator: tokio::sync::Mutex::new((StateEstimator::new(0.02, 0.995), Instant::now())),
+            joint_name_to_id: ACTUATOR_NAME_TO_ID
+                .iter()
+                .map(|(name, id)| (name.to_string(), *id))
+                .collect(),
+            home_position: HOME_POSITION.iter().map(|(id, pos)| (*id as u32, *pos)).collect(),
         })
     }
 
+    /// Builds a provider from a `GainTable` (see `config::load_gain_table`)
+    /// instead of the compiled `ACTUATOR_KP_KD`/`ACTUATOR_NAME_TO_ID`/
+    /// `HOME_POSITION` constants, so actuator gains, limits, joint mapping,
+    /// and bus wiring can all be tuned from a file without a recompile.
+    pub async fn from_config(
+        table: &GainTable,
+        torque_enabled: bool,
+        torque_scale: f32,
+    ) -> Result<Self, ModelError> {
+        let kbot_actuators = Actuator::kbot_actuators_from_gain_entries(&table.actuators)
+            .map_err(|e| ModelError::Provider(e.to_string()))?;
+
+        let can_ports: Vec<&str> = table.bus.can_ports.iter().map(String::as_str).collect();
+        let imu_devices: Vec<&str> = table.bus.imu_devices.iter().map(String::as_str).collect();
+
+        let (imu, actuators) = tokio::try_join!(
+            IMU::new(&imu_devices, table.bus.imu_baud),
+            Actuator::new(can_ports, Duration::from_millis(100), &kbot_actuators, None)
+        )
+        .map_err(|e| ModelError::Provider(e.to_string()))?;
+
+        for entry in &table.actuators {
+            if let Err(e) = actuators
+                .configure_actuator(ConfigureRequest {
+                    actuator_id: entry.id,
+                    kp: Some(entry.kp),
+                    kd: Some(entry.kd),
+                    max_torque: Some(entry.max_torque * torque_scale),
+                    torque_enabled: Some(torque_enabled),
+                    zero_position: None,
+                    new_actuator_id: None,
+                    max_velocity: entry.max_velocity,
+                    max_current: entry.max_current,
+                })
+                .await
+            {
+                tracing::warn!("Failed to configure torque on actuator {}: {}", entry.id, e);
+            }
+        }
+
+        Ok(Self {
+            actuators,
+            imu,
+            start_time: Instant::now(),
+            initial_heading: std::sync::OnceLock::new(),
+            command_source: Arc::new(Mutex::new(Vec::new())),
+            smoothed_command: Mutex::new(Vec::new()),
+            command_deadband: 0.01,
+            command_smoothing_alpha: 0.3,
+            expected_num_commands: std::sync::OnceLock::new(),
+            kinematics: None,
+            state_estimator: tokio::sync::Mutex::new((StateEstimator::new(0.02, 0.995), Instant::now())),
+            home_position: table
+                .home_position
+                .iter()
+                .map(|entry| (entry.id, entry.position))
+                .collect(),
+            joint_name_to_id: table
+                .actuators
+                .iter()
+                .map(|entry| (entry.name.clone(), entry.id))
+                .collect(),
+        })
+    }
+
+    /// Current fused base orientation/angular-velocity estimate, updated on
+    /// every `get_inputs` hardware read. The friction-compensated joint
+    /// torques computed on those reads are not returned here. See
+    /// `state_estimator::StateEstimator` for why these aren't yet threaded
+    /// through as new `InputType`s.
+    pub async fn get_base_state(&self) -> (Quaternion, Vector3) {
+        let estimator = self.state_estimator.lock().await;
+        (estimator.0.orientation(), estimator.0.angular_velocity())
+    }
+
+    /// Loads the forward-kinematics chain definitions used by
+    /// `get_feet_positions` and `get_center_of_mass`.
+    pub fn load_kinematics(&mut self, path: &std::path::Path) -> Result<(), ModelError> {
+        self.kinematics =
+            Some(KinematicsModel::load(path).map_err(|e| ModelError::Provider(e.to_string()))?);
+        Ok(())
+    }
+
+    async fn joint_angles_map(
+        &self,
+        joint_names: &[String],
+    ) -> Result<HashMap<String, f64>, ModelError> {
+        let actuator_ids = self.get_actuator_ids(joint_names)?;
+        let states = self.get_actuator_state(&actuator_ids).await?;
+        Ok(joint_names
+            .iter()
+            .zip(states.iter())
+            .filter_map(|(name, state)| state.position.map(|p| (name.clone(), p)))
+            .collect())
+    }
+
+    /// Current Cartesian tip position of every configured kinematic chain
+    /// (e.g. each foot), keyed by chain name.
+    pub async fn get_feet_positions(
+        &self,
+        joint_names: &[String],
+    ) -> Result<HashMap<String, [f64; 3]>, ModelError> {
+        let kinematics = self
+            .kinematics
+            .as_ref()
+            .ok_or_else(|| ModelError::Provider("Kinematics model not loaded".into()))?;
+        let joint_angles = self.joint_angles_map(joint_names).await?;
+        Ok(kinematics.tip_positions(&joint_angles))
+    }
+
+    /// Current mass-weighted center of mass across every configured chain.
+    pub async fn get_center_of_mass(
+        &self,
+        joint_names: &[String],
+    ) -> Result<[f64; 3], ModelError> {
+        let kinematics = self
+            .kinematics
+            .as_ref()
+            .ok_or_else(|| ModelError::Provider("Kinematics model not loaded".into()))?;
+        let joint_angles = self.joint_angles_map(joint_names).await?;
+        Ok(kinematics.center_of_mass(&joint_angles))
+    }
+
+    /// Sets the deadband and exponential smoothing factor applied to
+    /// `set_command` pushes before they reach the policy.
+    pub fn set_command_smoothing(&mut self, deadband: f32, alpha: f32) {
+        assert!(alpha > 0.0 && alpha <= 1.0);
+        self.command_deadband = deadband;
+        self.command_smoothing_alpha = alpha;
+    }
+
+    /// Pushes a new command setpoint (e.g. x/y velocity and yaw-rate from a
+    /// gamepad or network task), read by the next `Command` input request.
+    /// Rejected if it doesn't match the length already observed from
+    /// `metadata.num_commands`.
+    pub fn set_command(&self, cmd: Vec<f32>) -> Result<(), ModelError> {
+        if let Some(expected) = self.expected_num_commands.get() {
+            if cmd.len() != *expected {
+                return Err(ModelError::Provider(format!(
+                    "Command length {} does not match expected length {}",
+                    cmd.len(),
+                    expected
+                )));
+            }
+        }
+        *self.command_source.lock().expect("command_source lock poisoned") = cmd;
+        Ok(())
+    }
+
     fn get_actuator_ids(&self, joint_names: &[String]) -> Result<Vec<u32>, ModelError> {
         joint_names
             .iter()
             .map(|name| {
-                ACTUATOR_NAME_TO_ID
+                self.joint_name_to_id
                     .iter()
-                    .find(|(const_name, _)| *name == *const_name)
+                    .find(|(mapped_name, _)| mapped_name == name)
                     .map(|(_, id)| *id)
                     .ok_or_else(|| ModelError::Provider(format!("Joint name not found: {}", name)))
             })
@@ -118,23 +317,23 @@ impl KBotProvider {
             let mut ret = 0.0f64;
 
             let states = self.actuators.get_actuators_state(
-                HOME_POSITION.iter().map(|(id, _)| *id as u32).collect::<Vec<u32>>(),
+                self.home_position.iter().map(|(id, _)| *id).collect::<Vec<u32>>(),
             ).await.map_err(|e| ModelError::Provider(e.to_string()))?;
 
             let mut commands = vec![];
-            for (id, target) in HOME_POSITION {
-                let state = states.iter().find(|state| state.actuator_id == id as u32).expect("Actuator in HOME_POSITION not found in states");
+            for (id, target) in &self.home_position {
+                let state = states.iter().find(|state| state.actuator_id == *id).expect("Actuator in home_position not found in states");
                 let Some(position) = state.position else {
                     continue; // Skip if position is None
                 };
 
-                let err = normalize_actuator_qpos(position) - target as f64;
+                let err = normalize_actuator_qpos(position) - *target as f64;
                 ret = ret.max(err.abs());
 
                 let step = err.clamp(-4.0f64.to_radians(), 4.0f64.to_radians());
 
                 commands.push(ActuatorCommand {
-                    actuator_id: id as u32,
+                    actuator_id: *id,
                     position: Some(position + step),
                     velocity: None,
                     torque: None,
@@ -156,6 +355,99 @@ impl KBotProvider {
 
         Ok(())
     }
+
+    /// `home_position`, as an array ordered and shaped like the joint-angle
+    /// vectors `ModelRuntime` passes to `take_action` (one entry per
+    /// `ACTUATOR_NAME_TO_ID` slot), for interpolating a model-driven ramp
+    /// back to home instead of `move_to_home`'s own position-command loop.
+    pub fn home_position_array(&self) -> Array<f32, IxDyn> {
+        let positions: Vec<f32> = ACTUATOR_NAME_TO_ID
+            .iter()
+            .map(|(_, id)| {
+                self.home_position
+                    .iter()
+                    .find(|(home_id, _)| home_id == id)
+                    .map(|(_, position)| *position)
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        Array::from_shape_vec((positions.len(),), positions)
+            .expect("home position vector length matches itself")
+            .into_dyn()
+    }
+
+    /// Immediately disables torque on every actuator, used as the last-resort
+    /// action behind a triple-tap e-stop gesture. Unlike a graceful ramped
+    /// stop, this cuts power where the joints currently are rather than
+    /// interpolating them to a safe pose first.
+    pub async fn emergency_stop(&self) -> Result<(), ModelError> {
+        for (_, id) in &self.joint_name_to_id {
+            if let Err(e) = self
+                .actuators
+                .configure_actuator(ConfigureRequest {
+                    actuator_id: *id,
+                    kp: None,
+                    kd: None,
+                    max_torque: None,
+                    torque_enabled: Some(false),
+                    zero_position: None,
+                    new_actuator_id: None,
+                    max_velocity: None,
+                    max_current: None,
+                })
+                .await
+            {
+                tracing::error!("Failed to disable torque on actuator {} during e-stop: {}", id, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes every `table.actuators` entry's kp/kd/torque/velocity/current
+    /// limits down to the actuator driver, e.g. after `GainStore` hot-reloads
+    /// a changed config file on SIGHUP — otherwise a "reload" only swaps the
+    /// in-memory table and never reaches hardware. An entry for an actuator
+    /// ID this provider doesn't know about is skipped with a warning rather
+    /// than failing the whole reload.
+    pub async fn apply_gain_table(&self, table: &GainTable) -> Result<(), ModelError> {
+        let known_ids: std::collections::HashSet<u32> =
+            self.joint_name_to_id.iter().map(|(_, id)| *id).collect();
+
+        for entry in &table.actuators {
+            if !known_ids.contains(&entry.id) {
+                tracing::warn!(
+                    "Reloaded gain table has an entry for unknown actuator {}, skipping",
+                    entry.id
+                );
+                continue;
+            }
+
+            let response = self
+                .actuators
+                .configure_actuator(ConfigureRequest {
+                    actuator_id: entry.id,
+                    kp: Some(entry.kp),
+                    kd: Some(entry.kd),
+                    max_torque: Some(entry.max_torque),
+                    torque_enabled: None,
+                    zero_position: None,
+                    new_actuator_id: None,
+                    max_velocity: entry.max_velocity,
+                    max_current: entry.max_current,
+                })
+                .await
+                .map_err(|e| ModelError::Provider(e.to_string()))?;
+
+            if !response.success {
+                tracing::warn!(
+                    "Failed to apply reloaded gains to actuator {}: {:?}",
+                    entry.id,
+                    response.error
+                );
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -180,6 +472,26 @@ impl ModelProvider for KBotProvider {
             }
         )?;
 
+        // Drive the base-state estimator from the same hardware read, so its
+        // orientation/friction estimates stay in lockstep with the policy's
+        // own inputs rather than needing a separate polling task.
+        {
+            let mut guard = self.state_estimator.lock().await;
+            let (estimator, last_update) = &mut *guard;
+            let dt = last_update.elapsed().as_secs_f32();
+            *last_update = Instant::now();
+
+            let gyro = Vector3::new(imu_values.gyro_x, imu_values.gyro_y, imu_values.gyro_z);
+            let accel = Vector3::new(imu_values.accel_x, imu_values.accel_y, imu_values.accel_z);
+            estimator.update_orientation(gyro, accel, dt);
+
+            for state in &act_state {
+                if let (Some(velocity), Some(torque)) = (state.velocity, state.torque) {
+                    estimator.compensate_torque(state.actuator_id, velocity, torque);
+                }
+            }
+        }
+
         // Populate the requested slots
         let mut out = HashMap::with_capacity(input_types.len());
 
@@ -219,8 +531,14 @@ impl ModelProvider for KBotProvider {
                 Carry => {
                     return Err(ModelError::Provider("Carry should come via step()".into()));
                 },
-                InitialHeading => todo!(),
-                Quaternion => todo!(),
+                InitialHeading => {
+                    let arr = self.get_initial_heading_from_values(&imu_values)?;
+                    out.insert(InitialHeading, arr);
+                }
+                Quaternion => {
+                    let arr = self.get_quaternion_from_values(&imu_values)?;
+                    out.insert(Quaternion, arr);
+                }
             }
         }
 
@@ -239,13 +557,14 @@ impl ModelProvider for KBotProvider {
             .iter()
             .zip(action.iter())
             .map(|(name, action_value)| {
-                let id = ACTUATOR_NAME_TO_ID
+                let id = self
+                    .joint_name_to_id
                     .iter()
-                    .find(|(const_name, _)| *name == *const_name)
+                    .find(|(mapped_name, _)| mapped_name == name)
                     .map(|(_, found_id)| *found_id)
                     .ok_or_else(|| {
                         ModelError::Provider(format!(
-                            "Joint name not found in ACTUATOR_NAME_TO_ID: {}",
+                            "Joint name not found in joint_name_to_id: {}",
                             name
                         ))
                     })?;
@@ -333,10 +652,10 @@ impl KBotProvider {
         imu_values: &crate::imu::IMUData,
     ) -> Result<Array<f32, IxDyn>, ModelError> {
         let projected_gravity = Quaternion {
-            x: imu_values.quat_x,
-            y: imu_values.quat_y,
-            z: imu_values.quat_z,
-            w: imu_values.quat_w,
+            x: imu_values.quat.x,
+            y: imu_values.quat.y,
+            z: imu_values.quat.z,
+            w: imu_values.quat.w,
         }
         .rotate_vector(Vector3::new(0.0, 0.0, -9.81), true);
         Ok(Array::from_shape_vec(
@@ -375,15 +694,138 @@ impl KBotProvider {
             .into_dyn())
     }
 
+    fn get_quaternion_from_values(
+        &self,
+        imu_values: &crate::imu::IMUData,
+    ) -> Result<Array<f32, IxDyn>, ModelError> {
+        // The raw device orientation, not the Madgwick-fused one `quat`
+        // carries — this is what the `Quaternion` input type documents.
+        let quat = &imu_values.raw_quat;
+        Ok(
+            Array::from_shape_vec((4,), vec![quat.x, quat.y, quat.z, quat.w])
+                .map_err(|e| ModelError::Provider(e.to_string()))?
+                .into_dyn(),
+        )
+    }
+
+    fn get_initial_heading_from_values(
+        &self,
+        imu_values: &crate::imu::IMUData,
+    ) -> Result<Array<f32, IxDyn>, ModelError> {
+        let heading = *self.initial_heading.get_or_init(|| {
+            // yaw = atan2(2*(w*z + x*y), 1 - 2*(y*y + z*z)), i.e. the yaw
+            // component of the same Euler extraction used elsewhere.
+            crate::imu::quat_to_euler(imu_values.quat).z
+        });
+        Ok(Array::from_shape_vec((1,), vec![heading])
+            .map_err(|e| ModelError::Provider(e.to_string()))?
+            .into_dyn())
+    }
+
     fn get_command_internal(
         &self,
         metadata: &ModelMetadata,
     ) -> Result<Array<f32, IxDyn>, ModelError> {
-        // For now, return zeros for command input
         let num_commands = metadata.num_commands.unwrap_or(0);
-        let command_values = vec![0.0f32; num_commands];
+        self.expected_num_commands.get_or_init(|| num_commands);
+
+        let raw = self
+            .command_source
+            .lock()
+            .expect("command_source lock poisoned");
+        let target = pad_command(&raw, num_commands);
+        drop(raw);
+
+        // Deadband + exponential smoothing so a pushed setpoint doesn't
+        // appear to the policy as a step discontinuity.
+        let mut smoothed = self
+            .smoothed_command
+            .lock()
+            .expect("smoothed_command lock poisoned");
+        smooth_command(
+            &target,
+            &mut smoothed,
+            self.command_deadband,
+            self.command_smoothing_alpha,
+        );
+        let command_values = smoothed.clone();
+
         Ok(Array::from_shape_vec((num_commands,), command_values)
             .map_err(|e| ModelError::Provider(e.to_string()))?
             .into_dyn())
     }
 }
+
+/// Zero-pads or truncates `raw` (whatever `set_command` last pushed) to
+/// `num_commands` (whatever the model metadata declares). `set_command`
+/// rejects a length mismatch only once `num_commands` is known, so a push
+/// accepted before then can differ in length; it still yields a
+/// `num_commands`-long input.
+fn pad_command(raw: &[f32], num_commands: usize) -> Vec<f32> {
+    let mut target = vec![0.0f32; num_commands];
+    for (slot, value) in target.iter_mut().zip(raw.iter()) {
+        *slot = *value;
+    }
+    target
+}
+
+/// Deadband + exponential smoothing so a pushed setpoint doesn't appear to
+/// the policy as a step discontinuity; `smoothed` is resized to match
+/// `target` on a command-length change (e.g. the very first call).
+fn smooth_command(target: &[f32], smoothed: &mut Vec<f32>, deadband: f32, alpha: f32) {
+    if smoothed.len() != target.len() {
+        *smoothed = vec![0.0f32; target.len()];
+    }
+    for (current, target) in smoothed.iter_mut().zip(target.iter()) {
+        let delta = target - *current;
+        if delta.abs() > deadband {
+            *current += delta * alpha;
+        } else {
+            *current = *target;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exercises the same pipeline `set_command` -> `get_command_internal`
+    /// drives each tick: a pushed setpoint should reach the policy's
+    /// `Command` input, ramping in via the deadband/smoothing filter rather
+    /// than staying stuck at zero (the bug this pipeline was wired up to
+    /// fix: `set_command` previously had no caller, so W/A/S/D and teleop
+    /// input never reached the model at all).
+    #[test]
+    fn set_command_reaches_the_smoothed_output() {
+        let deadband = 0.01;
+        let alpha = 0.5;
+        let mut smoothed = Vec::new();
+
+        // Nothing pushed yet: smoothed output for 3 commands stays at zero.
+        let target = pad_command(&[], 3);
+        smooth_command(&target, &mut smoothed, deadband, alpha);
+        assert_eq!(smoothed, vec![0.0, 0.0, 0.0]);
+
+        // A forward-walk command pushed via `set_command` should start
+        // moving the smoothed output toward it on the very next tick...
+        let pushed = vec![1.0, 0.0, 0.0];
+        let target = pad_command(&pushed, 3);
+        smooth_command(&target, &mut smoothed, deadband, alpha);
+        assert!(smoothed[0] > 0.0 && smoothed[0] < 1.0);
+        assert_eq!(smoothed[1], 0.0);
+        assert_eq!(smoothed[2], 0.0);
+
+        // ...and converge to it after enough ticks.
+        for _ in 0..50 {
+            smooth_command(&target, &mut smoothed, deadband, alpha);
+        }
+        assert!((smoothed[0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pad_command_truncates_and_zero_pads() {
+        assert_eq!(pad_command(&[1.0, 2.0, 3.0, 4.0], 2), vec![1.0, 2.0]);
+        assert_eq!(pad_command(&[1.0], 3), vec![1.0, 0.0, 0.0]);
+    }
+}
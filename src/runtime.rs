@@ -1,22 +1,34 @@
 use ::kinfer::model::{ModelError, ModelRunner};
 use ::ndarray::Array;
-use ::std::sync::atomic::{AtomicBool, Ordering};
+use ::std::future::Future;
+use ::std::path::PathBuf;
+use ::std::pin::Pin;
+use ::std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use ::std::sync::Arc;
 use ::std::time::Duration;
 use ::tokio::runtime::Runtime;
-use ::tokio::time::sleep;
-use nix::sys::timerfd::{ClockId, Expiration, TimerFd, TimerFlags, TimerSetTimeFlags};
+use ::tokio::sync::Mutex as AsyncMutex;
 
+use crate::behavior::BehaviorMachine;
+use crate::clock::{ClockSource, TimerFdClock};
+use crate::config::BehaviorConfig;
 use crate::constants::ACTUATOR_NAME_TO_ID;
 use crate::keyboard;
+use crate::playback::{CommandRecorder, CommandReplayer};
 use crate::provider::KBotProvider;
+use crate::scheduler::{PeriodicCallback, TimerWheel};
 use std::time::SystemTime;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 // We trigger a read N milliseconds before reading the current actuator state,
 // to account for the asynchronicity of the CAN RX buffer.
 const TRIGGER_READ_BEFORE: Duration = Duration::from_millis(2);
 
+// Resolution of the periodic-task scheduler's timer wheel. Independent of
+// `dt`, since tasks registered via `add_periodic_task` (telemetry, health
+// checks, watchdogs) run at their own cadence, not the model's.
+const SCHEDULER_TICK: Duration = Duration::from_millis(5);
+
 pub struct ModelRuntime {
     model_provider: Arc<KBotProvider>,
     model_runner: Arc<ModelRunner>,
@@ -26,6 +38,26 @@ pub struct ModelRuntime {
     running: Arc<AtomicBool>,
     runtime: Option<Runtime>,
     keyboard_enabled: bool,
+    command_recorder: Option<Arc<AsyncMutex<CommandRecorder>>>,
+    command_replayer: Option<Arc<CommandReplayer>>,
+    behavior: Option<Arc<AsyncMutex<BehaviorMachine>>>,
+    /// Total number of missed `dt` deadlines, summed across every tick whose
+    /// clock-source expiration counter was greater than 1.
+    missed_deadlines: Arc<AtomicU64>,
+    /// Largest number of consecutively-missed deadlines observed on a single
+    /// tick, i.e. the worst-case overrun.
+    worst_overrun_ticks: Arc<AtomicU64>,
+    /// Time source driving the control loop. Defaults to `TimerFdClock`
+    /// (built lazily in `start`, since it needs `dt`); `set_clock_source`
+    /// overrides it, e.g. with a `MockClock` in a test harness.
+    clock_source: Option<Arc<dyn ClockSource>>,
+    /// Periodic jobs registered via `add_periodic_task`, not yet handed to a
+    /// `TimerWheel` (which can only be built once `start` is running inside
+    /// the tokio runtime).
+    pending_periodic_tasks: Vec<(Duration, Duration, PeriodicCallback)>,
+    /// Set by `stop_graceful`; consumed by the control loop, which performs
+    /// the ramp itself before clearing `running`.
+    graceful_stop_request: Arc<std::sync::Mutex<Option<Duration>>>,
 }
 
 impl ModelRuntime {
@@ -46,9 +78,57 @@ impl ModelRuntime {
             running: Arc::new(AtomicBool::new(false)),
             runtime: None,
             keyboard_enabled,
+            command_recorder: None,
+            command_replayer: None,
+            behavior: None,
+            missed_deadlines: Arc::new(AtomicU64::new(0)),
+            worst_overrun_ticks: Arc::new(AtomicU64::new(0)),
+            clock_source: None,
+            pending_periodic_tasks: Vec::new(),
+            graceful_stop_request: Arc::new(std::sync::Mutex::new(None)),
         }
     }
 
+    /// Overrides the control loop's time source, e.g. with a `MockClock` so
+    /// a test harness can drive `step` -> interpolate -> `take_action` ->
+    /// trigger-read tick-by-tick instead of against a real timerfd. Falls
+    /// back to `TimerFdClock` when never called.
+    pub fn set_clock_source(&mut self, clock_source: Arc<dyn ClockSource>) {
+        self.clock_source = Some(clock_source);
+    }
+
+    /// Enables the startup/idle keyframe state machine, driving the
+    /// `KEYFRAME_INDEX` command slot automatically instead of only via
+    /// number keys.
+    pub fn set_behavior_config(&mut self, config: BehaviorConfig) {
+        self.behavior = Some(Arc::new(AsyncMutex::new(BehaviorMachine::new(config))));
+    }
+
+    /// Records every control tick's command vector to `path` for later
+    /// replay. If `enable_command_replay` is also set, the replayed commands
+    /// are what gets recorded.
+    pub fn enable_command_recording(&mut self, path: PathBuf) -> Result<(), ModelError> {
+        let recorder = CommandRecorder::new(&path)
+            .map_err(|e| ModelError::Provider(format!("Failed to open command log: {}", e)))?;
+        self.command_recorder = Some(Arc::new(AsyncMutex::new(recorder)));
+        Ok(())
+    }
+
+    /// Replays a previously recorded command log instead of live keyboard
+    /// input; `rate` is a playback-rate multiplier composed with
+    /// `slowdown_factor`, and `looping` restarts the recording once it ends.
+    pub fn enable_command_replay(
+        &mut self,
+        path: PathBuf,
+        rate: f32,
+        looping: bool,
+    ) -> Result<(), ModelError> {
+        let replayer = CommandReplayer::load(&path, rate, looping)
+            .map_err(|e| ModelError::Provider(format!("Failed to load command log: {}", e)))?;
+        self.command_replayer = Some(Arc::new(replayer));
+        Ok(())
+    }
+
     pub fn set_slowdown_factor(&mut self, slowdown_factor: i32) {
         assert!(slowdown_factor >= 1);
         self.slowdown_factor = slowdown_factor;
@@ -60,6 +140,47 @@ impl ModelRuntime {
         self.magnitude_factor = magnitude_factor;
     }
 
+    /// How long a held movement/orientation key must be down before it
+    /// starts autorepeating; see `keyboard::apply_key_press`. Default 250ms.
+    pub fn set_repeat_delay(&mut self, delay: Duration) {
+        keyboard::set_repeat_delay(delay);
+    }
+
+    /// Spacing between autorepeat ticks once a held key starts repeating.
+    /// Default 30ms.
+    pub fn set_repeat_period(&mut self, period: Duration) {
+        keyboard::set_repeat_period(period);
+    }
+
+    /// Registers `callback` to run every `period`, first firing `offset`
+    /// after `start`, from a shared timer-wheel scheduler rather than a
+    /// hand-coded `sleep` of its own. Intended for cross-cutting jobs that
+    /// don't feed into the model step/interpolate/take_action sequence —
+    /// telemetry at a slower cadence, a watchdog, battery polling, health
+    /// checks. Must be called before `start`.
+    pub fn add_periodic_task<F, Fut>(&mut self, period: Duration, offset: Duration, callback: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let callback: PeriodicCallback = Arc::new(move || -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            Box::pin(callback())
+        });
+        self.pending_periodic_tasks.push((period, offset, callback));
+    }
+
+    /// Total number of missed `dt` deadlines since `start`, summed across
+    /// every tick whose timerfd expiration counter was greater than 1.
+    pub fn missed_deadline_count(&self) -> u64 {
+        self.missed_deadlines.load(Ordering::Relaxed)
+    }
+
+    /// Worst-case overrun observed on a single tick, as a duration (the
+    /// deepest consecutive-deadline-miss multiplied by `dt`).
+    pub fn worst_overrun(&self) -> Duration {
+        self.dt * self.worst_overrun_ticks.load(Ordering::Relaxed) as u32
+    }
+
     pub fn start(&mut self) -> Result<(), ModelError> {
         if self.running.load(Ordering::Relaxed) {
             return Ok(());
@@ -72,6 +193,20 @@ impl ModelRuntime {
         let slowdown_factor = self.slowdown_factor;
         let magnitude_factor = self.magnitude_factor;
         let keyboard_enabled = self.keyboard_enabled;
+        let command_recorder = self.command_recorder.clone();
+        let command_replayer = self.command_replayer.clone();
+        let behavior = self.behavior.clone();
+        let missed_deadlines = self.missed_deadlines.clone();
+        let worst_overrun_ticks = self.worst_overrun_ticks.clone();
+        let pending_periodic_tasks = std::mem::take(&mut self.pending_periodic_tasks);
+        let graceful_stop_request = self.graceful_stop_request.clone();
+        let clock: Arc<dyn ClockSource> = match &self.clock_source {
+            Some(clock) => clock.clone(),
+            None => Arc::new(
+                TimerFdClock::new(dt)
+                    .map_err(|e| ModelError::Provider(format!("Failed to set timer: {}", e)))?,
+            ),
+        };
 
         let runtime = Runtime::new()?;
         running.store(true, Ordering::Relaxed);
@@ -79,6 +214,13 @@ impl ModelRuntime {
         runtime.spawn(async move {
             info!("Starting model runtime");
 
+            // Hand every task registered via `add_periodic_task` to a single
+            // timer-wheel scheduler instead of each growing its own sleep.
+            let timer_wheel = TimerWheel::new(SCHEDULER_TICK);
+            for (period, offset, callback) in pending_periodic_tasks {
+                timer_wheel.add_periodic_task(period, offset, callback).await;
+            }
+
             println!("Press enter to Home...");
             let mut input = String::new();
             std::io::stdin().read_line(&mut input).unwrap();
@@ -95,9 +237,13 @@ impl ModelRuntime {
                 println!("Keyboard controls are now active! Use ESC to exit or Ctrl+C.");
             }
 
+            // While a recorded trajectory is replaying, live keyboard input
+            // drives nothing except the ESC shutdown key.
+            keyboard::set_replay_active(command_replayer.is_some());
+
             for i in 1..5 {
                 println!("Starting in {} seconds...", 5 - i);
-                sleep(Duration::from_secs(1)).await;
+                clock.sleep(Duration::from_secs(1)).await;
             }
 
             let mut carry = model_runner
@@ -123,20 +269,28 @@ impl ModelRuntime {
                     .into_dyn()
             };
 
-            // Wait for the first tick, since it happens immediately.
-            let read_interval =
-                TimerFd::new(ClockId::CLOCK_MONOTONIC, TimerFlags::empty()).unwrap();
-            read_interval
-                .set(Expiration::Interval(dt.into()), TimerSetTimeFlags::empty())
-                .map_err(|e| ModelError::Provider(format!("Failed to set timer: {}", e)))?;
-
             // Start the two intervals N milliseconds apart. The first tick is
             // always instantaneous and represents the start of the interval
             // ticks.
-            read_interval
-                .wait()
+            clock
+                .tick()
+                .await
                 .map_err(|e| ModelError::Provider(format!("Failed to wait for timer: {}", e)))?;
 
+            // Rebase the recorder's/replayer's wall clock to this point,
+            // right before the control loop actually starts consuming
+            // ticks. Without this, the operator-paced Home/start prompts
+            // and the countdown above would get folded into the first
+            // recorded/replayed timestamp, and that pause differs between
+            // a record session and a replay session — breaking
+            // bit-for-bit reproducibility.
+            if let Some(recorder) = &command_recorder {
+                recorder.lock().await.reset_start();
+            }
+            if let Some(replayer) = &command_replayer {
+                replayer.reset_start();
+            }
+
             info!("Entering main control loop");
             while running.load(Ordering::Relaxed) {
                 let uuid = uuid::Uuid::new_v4();
@@ -148,18 +302,99 @@ impl ModelRuntime {
                     uuid_main_control_loop
                 );
 
-                let (output, next_carry) = model_runner
-                    .step(carry)
-                    .await
-                    .map_err(|e| ModelError::Provider(e.to_string()))?;
-                carry = next_carry;
+                // Service the tap-dance gesture bound in `keyboard`: a
+                // double tap sends the robot home, a triple tap (or more)
+                // forces an e-stop and halts the loop; a single tap toggles
+                // the pause flag checked just below.
+                if keyboard::take_home_requested() {
+                    info!("Tap-dance: moving to home position");
+                    model_provider.move_to_home().await?;
+                }
+                if keyboard::take_estop_requested() {
+                    warn!("Tap-dance: emergency stop requested, disabling torque");
+                    model_provider.emergency_stop().await?;
+                    running.store(false, Ordering::Relaxed);
+                    break;
+                }
+                if let Some(ramp_duration) = graceful_stop_request
+                    .lock()
+                    .expect("graceful stop lock poisoned")
+                    .take()
+                {
+                    info!("Graceful stop requested; ramping to home over {:?}", ramp_duration);
+                    let ramp_steps = (ramp_duration.as_secs_f64() / dt.as_secs_f64()).ceil().max(1.0) as i32;
+                    let target = model_provider.home_position_array();
+                    for step in 1..=ramp_steps {
+                        let t = step as f32 / ramp_steps as f32;
+                        let interp_joint_positions = &joint_positions * (1.0 - t) + &target * t;
+                        model_runner
+                            .take_action(interp_joint_positions * magnitude_factor)
+                            .await
+                            .map_err(|e| ModelError::Provider(e.to_string()))?;
+                        clock.sleep(dt).await;
+                    }
+                    running.store(false, Ordering::Relaxed);
+                    break;
+                }
+                if keyboard::is_paused() {
+                    clock.tick().await.map_err(|e| {
+                        ModelError::Provider(format!("Failed to wait for timer: {}", e))
+                    })?;
+                    continue;
+                }
+
+                if let Some(replayer) = &command_replayer {
+                    keyboard::set_all_commands(replayer.commands());
+                    if replayer.is_finished() {
+                        info!("Command replay finished, stopping runtime");
+                        running.store(false, Ordering::Relaxed);
+                    }
+                }
+                if let Some(recorder) = &command_recorder {
+                    let mut recorder = recorder.lock().await;
+                    if let Err(e) = recorder.record_tick(keyboard::get_commands()) {
+                        warn!("Failed to record command tick: {}", e);
+                    }
+                }
+
+                // Push the x/y/yaw-rate locomotion slots (keyboard, teleop,
+                // or a replayed recording, whichever last wrote them) into
+                // the policy's `Command` input. Without this the model never
+                // actually saw driven input: `keyboard`/`teleop` only ever
+                // wrote to the atomics backing `get_commands`, which fed the
+                // recorder/replayer/idle-behavior detector but never
+                // `KBotProvider::set_command`.
+                if let Err(e) = model_provider.set_command(keyboard::get_commands()[..3].to_vec())
+                {
+                    warn!("Failed to push locomotion command: {}", e);
+                }
+                // While the behavior machine owns the keyframe slot (startup
+                // ramp or idle hold), hold the last commanded joint
+                // positions instead of handing the policy's own action to
+                // the actuators this tick.
+                let held_by_behavior = match &behavior {
+                    Some(behavior) => behavior.lock().await.tick(),
+                    None => false,
+                };
+
+                let output = if held_by_behavior {
+                    joint_positions.clone()
+                } else {
+                    let (output, next_carry) = model_runner
+                        .step(carry)
+                        .await
+                        .map_err(|e| ModelError::Provider(e.to_string()))?;
+                    carry = next_carry;
+                    output
+                };
                 debug!(
                     "runtime::model_runner_step::END uuid={}, elapsed: {:?}",
                     uuid,
                     start.elapsed()
                 );
 
-                for i in 1..(slowdown_factor + 1) {
+                let mut i = 1;
+                while i <= slowdown_factor {
                     if !running.load(Ordering::Relaxed) {
                         break;
                     }
@@ -173,11 +408,28 @@ impl ModelRuntime {
                     // Trigger an actuator read N milliseconds before the next
                     // command tick, to make sure the observations are as fresh
                     // as possible.
-                    read_interval.wait().map_err(|e| {
+                    let elapsed_ticks = clock.tick().await.map_err(|e| {
                         ModelError::Provider(format!("Failed to wait for timer: {}", e))
                     })?;
                     model_provider.trigger_actuator_read().await?;
-                    sleep(TRIGGER_READ_BEFORE).await;
+                    clock.sleep(TRIGGER_READ_BEFORE).await;
+
+                    // `elapsed_ticks > 1` means the kernel coalesced one or
+                    // more missed `dt` deadlines into this wakeup, i.e. the
+                    // previous `take_action`/model `step` ran long. Skip the
+                    // corresponding number of interpolation sub-steps to
+                    // catch back up to real time instead of silently
+                    // drifting behind it.
+                    let missed = elapsed_ticks.saturating_sub(1);
+                    if missed > 0 {
+                        missed_deadlines.fetch_add(missed, Ordering::Relaxed);
+                        worst_overrun_ticks.fetch_max(missed, Ordering::Relaxed);
+                        warn!(
+                            "Missed {} control-loop deadline(s) (dt={:?}); skipping {} interpolation sub-step(s)",
+                            missed, dt, missed
+                        );
+                    }
+                    i += 1 + missed as i32;
                 }
 
                 joint_positions = output;
@@ -202,4 +454,22 @@ impl ModelRuntime {
             runtime.shutdown_background();
         }
     }
+
+    /// Like `stop`, but instead of abandoning the robot at whatever joint
+    /// command was last sent, first ramps `take_action` from the last
+    /// commanded joint positions to the home pose over `ramp_duration`
+    /// (rounded up to a whole number of `dt` steps), then tears the runtime
+    /// down. Blocks until the ramp completes.
+    pub fn stop_graceful(&mut self, ramp_duration: Duration) {
+        info!("Requesting graceful stop over {:?}", ramp_duration);
+        *self
+            .graceful_stop_request
+            .lock()
+            .expect("graceful stop lock poisoned") = Some(ramp_duration);
+
+        while self.running.load(Ordering::Relaxed) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        self.stop();
+    }
 }
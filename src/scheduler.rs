@@ -0,0 +1,131 @@
+//! Hashed timer-wheel scheduler for independent periodic tasks (telemetry,
+//! watchdogs, health/battery polling), modeled on mio's timer wheel: a ring
+//! of slots advanced one per `tick_period`, with each timeout hashed into
+//! `slot = target_tick & mask` so arming, firing, and re-arming a timeout
+//! are all O(1) regardless of how many tasks are registered, instead of
+//! growing a new hand-interleaved `sleep` for every cross-cutting behavior.
+//!
+//! The model-inference tick itself (`step` -> interpolate -> `take_action`
+//! -> trigger-read in `ModelRuntime::start`) stays its own tight loop rather
+//! than a wheel entry, since each of those calls feeds data (carry, output,
+//! joint positions) to the next one — `add_periodic_task` is for the
+//! side-effect-only jobs layered on top of it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex as AsyncMutex;
+
+/// A registered periodic job: an async closure with no inputs or outputs,
+/// run for its side effects (logging, a health check, a battery poll).
+pub type PeriodicCallback = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Number of slots in the wheel; a power of two so hashing a tick into a
+/// slot is a cheap bitmask instead of a modulo.
+const NUM_SLOTS: usize = 256;
+const SLOT_MASK: u64 = (NUM_SLOTS - 1) as u64;
+
+struct ScheduledTask {
+    period_ticks: u64,
+    next_fire_tick: u64,
+    callback: PeriodicCallback,
+}
+
+/// Drives every registered periodic task from one ticking loop instead of a
+/// separate `sleep` per task.
+pub struct TimerWheel {
+    tick_period: Duration,
+    slots: Vec<AsyncMutex<Vec<ScheduledTask>>>,
+    current_tick: AsyncMutex<u64>,
+}
+
+impl TimerWheel {
+    /// Builds a wheel and spawns its driver task; `tick_period` is the
+    /// wheel's own resolution; every registered task's `period`/`offset`
+    /// is rounded to the nearest multiple of it.
+    pub fn new(tick_period: Duration) -> Arc<Self> {
+        let wheel = Arc::new(Self {
+            tick_period,
+            slots: (0..NUM_SLOTS).map(|_| AsyncMutex::new(Vec::new())).collect(),
+            current_tick: AsyncMutex::new(0),
+        });
+        wheel.clone().spawn_driver();
+        wheel
+    }
+
+    /// Registers `callback` to fire every `period`, first firing `offset`
+    /// after registration. Phase relationships between independent jobs
+    /// (e.g. telemetry vs. a watchdog) are explicit in the `offset` passed
+    /// here, rather than emerging from the order sleeps happen to run in.
+    pub async fn add_periodic_task(&self, period: Duration, offset: Duration, callback: PeriodicCallback) {
+        let period_ticks = self.ticks(period).max(1);
+        let offset_ticks = self.ticks(offset);
+
+        let current = *self.current_tick.lock().await;
+        // An offset under one tick fires on the next tick; left at
+        // `current` it would hash to a slot the wheel has just passed.
+        let next_fire_tick = current + offset_ticks.max(1);
+        let task = ScheduledTask {
+            period_ticks,
+            next_fire_tick,
+            callback,
+        };
+
+        let slot = (next_fire_tick & SLOT_MASK) as usize;
+        self.slots[slot].lock().await.push(task);
+    }
+
+    fn ticks(&self, duration: Duration) -> u64 {
+        (duration.as_secs_f64() / self.tick_period.as_secs_f64()).round() as u64
+    }
+
+    fn spawn_driver(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.tick_period);
+            loop {
+                ticker.tick().await;
+                self.advance().await;
+            }
+        });
+    }
+
+    /// Advances the wheel by one tick, firing and re-arming every task
+    /// hashed into the slot for this tick.
+    async fn advance(&self) {
+        let tick = {
+            let mut current = self.current_tick.lock().await;
+            *current += 1;
+            *current
+        };
+        let slot = (tick & SLOT_MASK) as usize;
+
+        // A slot can hold tasks whose period collided into it from a
+        // different phase; only the ones actually due this tick fire, the
+        // rest go straight back into the bucket.
+        let due = {
+            let mut bucket = self.slots[slot].lock().await;
+            let mut due = Vec::new();
+            let mut remaining = Vec::new();
+            for task in bucket.drain(..) {
+                if task.next_fire_tick <= tick {
+                    due.push(task);
+                } else {
+                    remaining.push(task);
+                }
+            }
+            *bucket = remaining;
+            due
+        };
+
+        for mut task in due {
+            let callback = task.callback.clone();
+            tokio::spawn(async move { callback().await });
+
+            task.next_fire_tick = tick + task.period_ticks;
+            let next_slot = (task.next_fire_tick & SLOT_MASK) as usize;
+            self.slots[next_slot].lock().await.push(task);
+        }
+    }
+}
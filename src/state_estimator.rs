@@ -0,0 +1,257 @@
+//! Base-state estimation (orientation + angular velocity) fused from the
+//! IMU, plus online per-joint Coulomb/viscous friction estimation fit via
+//! recursive least squares (RLS) from commanded torque vs. measured
+//! velocity.
+//!
+//! Note: `kinfer::InputType` doesn't yet have variants for the filtered
+//! orientation/angular velocity or friction-compensated torques this
+//! produces, so it isn't wired into `KBotProvider::get_inputs` as new model
+//! inputs — once upstream adds them, read `StateEstimator::orientation` /
+//! `compensate_torque` from a match arm there, following the
+//! existing `JointAngles` pattern.
+
+use std::collections::HashMap;
+
+use imu::{Quaternion, Vector3};
+
+use crate::imu::rotate_quat;
+
+/// Above this deviation (m/s^2) from nominal gravity, the accelerometer
+/// reading is rejected as linear acceleration rather than tilt.
+const ACCEL_REJECTION_THRESHOLD: f32 = 0.3 * 9.81;
+
+fn normalize(q: Quaternion) -> Quaternion {
+    let norm = (q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+    if norm < 1e-9 {
+        return Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+    }
+    Quaternion {
+        w: q.w / norm,
+        x: q.x / norm,
+        y: q.y / norm,
+        z: q.z / norm,
+    }
+}
+
+/// Spherical linear interpolation between two unit quaternions.
+fn slerp(a: Quaternion, b: Quaternion, t: f32) -> Quaternion {
+    let mut b = b;
+    let mut dot = a.w * b.w + a.x * b.x + a.y * b.y + a.z * b.z;
+
+    // Take the shorter path around the hypersphere.
+    if dot < 0.0 {
+        b = Quaternion { w: -b.w, x: -b.x, y: -b.y, z: -b.z };
+        dot = -dot;
+    }
+
+    // Nearly parallel: linear interpolation avoids a division by ~0.
+    if dot > 0.9995 {
+        return normalize(Quaternion {
+            w: a.w + t * (b.w - a.w),
+            x: a.x + t * (b.x - a.x),
+            y: a.y + t * (b.y - a.y),
+            z: a.z + t * (b.z - a.z),
+        });
+    }
+
+    let theta_0 = dot.acos();
+    let theta = theta_0 * t;
+    let (sin_theta, sin_theta_0) = (theta.sin(), theta_0.sin());
+
+    let s_a = theta.cos() - dot * sin_theta / sin_theta_0;
+    let s_b = sin_theta / sin_theta_0;
+
+    normalize(Quaternion {
+        w: s_a * a.w + s_b * b.w,
+        x: s_a * a.x + s_b * b.x,
+        y: s_a * a.y + s_b * b.y,
+        z: s_a * a.z + s_b * b.z,
+    })
+}
+
+/// Quaternion whose rotation aligns `(0, 0, 1)` with the measured gravity
+/// direction, i.e. the orientation implied by the accelerometer alone.
+fn accel_to_quaternion(accel: Vector3) -> Option<Quaternion> {
+    let norm = (accel.x * accel.x + accel.y * accel.y + accel.z * accel.z).sqrt();
+    if norm < 1e-6 {
+        return None;
+    }
+    let (ax, ay, az) = (accel.x / norm, accel.y / norm, accel.z / norm);
+
+    // Rotation from the reference "up" vector (0, 0, 1) to the measured
+    // gravity direction, via the standard vector-to-quaternion formula.
+    let dot = az;
+    if dot > 0.9999 {
+        return Some(Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 });
+    }
+    if dot < -0.9999 {
+        return Some(Quaternion { w: 0.0, x: 1.0, y: 0.0, z: 0.0 });
+    }
+
+    let axis_x = -ay;
+    let axis_y = ax;
+    let w = 1.0 + dot;
+    Some(normalize(Quaternion { w, x: axis_x, y: axis_y, z: 0.0 }))
+}
+
+/// Per-joint Coulomb (`f_c`) + viscous (`f_v`) friction model,
+/// `tau_f = f_c * sign(qdot) + f_v * qdot`, fit online via recursive least
+/// squares from commanded torque vs. measured velocity.
+struct JointFrictionEstimator {
+    /// `[f_c, f_v]`.
+    theta: [f64; 2],
+    /// 2x2 covariance matrix.
+    p: [[f64; 2]; 2],
+    /// Forgetting factor in `(0, 1]`; lower adapts faster but noisier.
+    lambda: f64,
+}
+
+impl JointFrictionEstimator {
+    fn new(lambda: f64) -> Self {
+        Self {
+            theta: [0.0, 0.0],
+            p: [[1.0e6, 0.0], [0.0, 1.0e6]],
+            lambda,
+        }
+    }
+
+    /// Updates the friction estimate from one (velocity, torque) sample and
+    /// returns the current `(f_c, f_v)`.
+    fn update(&mut self, velocity: f64, measured_torque: f64) -> (f64, f64) {
+        let phi = [velocity.signum(), velocity];
+        let p_phi = [
+            self.p[0][0] * phi[0] + self.p[0][1] * phi[1],
+            self.p[1][0] * phi[0] + self.p[1][1] * phi[1],
+        ];
+        let denom = self.lambda + phi[0] * p_phi[0] + phi[1] * p_phi[1];
+        let k = [p_phi[0] / denom, p_phi[1] / denom];
+
+        let predicted = phi[0] * self.theta[0] + phi[1] * self.theta[1];
+        let error = measured_torque - predicted;
+        self.theta[0] += k[0] * error;
+        self.theta[1] += k[1] * error;
+
+        let kp = [
+            [k[0] * p_phi[0], k[0] * p_phi[1]],
+            [k[1] * p_phi[0], k[1] * p_phi[1]],
+        ];
+        self.p = [
+            [(self.p[0][0] - kp[0][0]) / self.lambda, (self.p[0][1] - kp[0][1]) / self.lambda],
+            [(self.p[1][0] - kp[1][0]) / self.lambda, (self.p[1][1] - kp[1][1]) / self.lambda],
+        ];
+
+        (self.theta[0], self.theta[1])
+    }
+
+    fn friction_torque(&self, velocity: f64) -> f64 {
+        self.theta[0] * velocity.signum() + self.theta[1] * velocity
+    }
+}
+
+/// Fuses gyro (prediction) with accelerometer-derived gravity (correction)
+/// into a base orientation estimate, and fits per-joint friction online.
+pub struct StateEstimator {
+    orientation: Quaternion,
+    angular_velocity: Vector3,
+    /// Blend factor toward the accel-corrected orientation each tick.
+    alpha: f32,
+    friction: HashMap<u32, JointFrictionEstimator>,
+    friction_lambda: f64,
+}
+
+impl StateEstimator {
+    pub fn new(alpha: f32, friction_lambda: f64) -> Self {
+        Self {
+            orientation: Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 },
+            angular_velocity: Vector3::new(0.0, 0.0, 0.0),
+            alpha,
+            friction: HashMap::new(),
+            friction_lambda,
+        }
+    }
+
+    pub fn orientation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    pub fn angular_velocity(&self) -> Vector3 {
+        self.angular_velocity
+    }
+
+    /// Propagates orientation by integrating the gyro over `dt`, then
+    /// corrects pitch/roll by blending toward the accel gravity direction
+    /// with gain `alpha`, rejecting the accel update under high
+    /// acceleration (the reading isn't then a reliable gravity reference).
+    pub fn update_orientation(&mut self, gyro: Vector3, accel: Vector3, dt: f32) {
+        self.angular_velocity = gyro;
+
+        let gyro_quat = Quaternion { w: 0.0, x: gyro.x, y: gyro.y, z: gyro.z };
+        let omega_term = rotate_quat(self.orientation, gyro_quat);
+        let predicted = normalize(Quaternion {
+            w: self.orientation.w + 0.5 * omega_term.w * dt,
+            x: self.orientation.x + 0.5 * omega_term.x * dt,
+            y: self.orientation.y + 0.5 * omega_term.y * dt,
+            z: self.orientation.z + 0.5 * omega_term.z * dt,
+        });
+
+        let accel_norm = (accel.x * accel.x + accel.y * accel.y + accel.z * accel.z).sqrt();
+        let high_acceleration = (accel_norm - 9.81).abs() > ACCEL_REJECTION_THRESHOLD;
+
+        self.orientation = match (high_acceleration, accel_to_quaternion(accel)) {
+            (false, Some(accel_quat)) => slerp(predicted, accel_quat, self.alpha),
+            _ => predicted,
+        };
+    }
+
+    /// Fits actuator `motor_id`'s friction model from one (velocity,
+    /// commanded torque) sample and returns the friction-compensated
+    /// torque, i.e. the commanded torque with the estimated friction term
+    /// removed.
+    pub fn compensate_torque(&mut self, motor_id: u32, velocity: f64, commanded_torque: f64) -> f64 {
+        let estimator = self
+            .friction
+            .entry(motor_id)
+            .or_insert_with(|| JointFrictionEstimator::new(self.friction_lambda));
+        estimator.update(velocity, commanded_torque);
+        commanded_torque - estimator.friction_torque(velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds noiseless samples generated from a known `f_c`/`f_v` pair
+    /// (alternating the sign of velocity so the two regressors,
+    /// `sign(qdot)` and `qdot`, aren't collinear) and checks the RLS fit
+    /// converges to that closed-form answer.
+    #[test]
+    fn friction_estimator_converges_to_the_generating_coefficients() {
+        let f_c = 2.0;
+        let f_v = 0.5;
+        let mut estimator = JointFrictionEstimator::new(1.0);
+
+        for i in 0..200 {
+            let velocity = if i % 2 == 0 { 1.0 } else { -1.5 };
+            let measured_torque = f_c * velocity.signum() + f_v * velocity;
+            estimator.update(velocity, measured_torque);
+        }
+
+        let (fc_est, fv_est) = (estimator.theta[0], estimator.theta[1]);
+        assert!((fc_est - f_c).abs() < 1e-6, "f_c: expected {}, got {}", f_c, fc_est);
+        assert!((fv_est - f_v).abs() < 1e-6, "f_v: expected {}, got {}", f_v, fv_est);
+    }
+
+    #[test]
+    fn friction_torque_matches_the_coulomb_viscous_model_once_converged() {
+        let mut estimator = JointFrictionEstimator::new(1.0);
+        for i in 0..200 {
+            let velocity = if i % 2 == 0 { 1.0 } else { -1.5 };
+            estimator.update(velocity, 2.0 * velocity.signum() + 0.5 * velocity);
+        }
+
+        let velocity = -0.75;
+        let expected = 2.0 * velocity.signum() + 0.5 * velocity;
+        assert!((estimator.friction_torque(velocity) - expected).abs() < 1e-6);
+    }
+}
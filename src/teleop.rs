@@ -0,0 +1,108 @@
+//! Networked teleoperation: feeds the same global command state consumed by
+//! `keyboard::get_commands()` over a TCP link, so an operator can drive the
+//! robot from another machine instead of requiring a local raw-mode terminal.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::keyboard;
+
+/// Number of `f32` command slots in a teleop frame: x, y, yaw_rate, yaw,
+/// height, roll, pitch, keyframe_index (matches `keyboard::get_commands`).
+const NUM_COMMAND_SLOTS: usize = 8;
+const FRAME_BYTES: usize = NUM_COMMAND_SLOTS * 4;
+
+static TELEOP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts the teleop TCP listener and its watchdog, running until
+/// `stop_teleop_listener` is called. Frames are eight little-endian `f32`s
+/// written straight into the command atomics shared with `keyboard`.
+pub async fn start_teleop_listener(addr: SocketAddr, command_timeout: Duration) -> Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Teleop listener bound to {}", addr);
+
+    TELEOP_RUNNING.store(true, Ordering::Relaxed);
+    let last_frame = Arc::new(Mutex::new(Instant::now()));
+
+    tokio::spawn(watchdog(last_frame.clone(), command_timeout));
+
+    while TELEOP_RUNNING.load(Ordering::Relaxed) {
+        let (socket, peer) = listener.accept().await?;
+        // Disable Nagle's algorithm: coalescing small teleop frames would add
+        // up to tens of milliseconds of latency, which we can't afford here.
+        socket.set_nodelay(true)?;
+        info!("Teleop client connected from {}", peer);
+
+        let last_frame = last_frame.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(socket, last_frame).await {
+                warn!("Teleop connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Clears the running flag: the listener loop exits after its next accepted
+/// connection and the watchdog after its next tick. Connection tasks that
+/// are already running are not stopped; each ends when its client
+/// disconnects.
+pub fn stop_teleop_listener() {
+    TELEOP_RUNNING.store(false, Ordering::Relaxed);
+}
+
+/// Zeroes all commands if no frame has arrived within `timeout`, so a dropped
+/// link doesn't leave the robot walking on a stale setpoint.
+async fn watchdog(last_frame: Arc<Mutex<Instant>>, timeout: Duration) {
+    // `interval` panics on a zero period; clamp instead of trusting a
+    // `--teleop-timeout-ms 0` from the CLI, since a dead watchdog task would
+    // silently leave a stale link driving the robot forever.
+    let tick_period = (timeout / 4).max(Duration::from_millis(1));
+    let mut tick = tokio::time::interval(tick_period);
+    while TELEOP_RUNNING.load(Ordering::Relaxed) {
+        tick.tick().await;
+        let elapsed = last_frame.lock().await.elapsed();
+        if elapsed > timeout {
+            warn!(
+                "Teleop watchdog: no frame received in {:?}, zeroing commands",
+                elapsed
+            );
+            keyboard::set_all_commands([0.0; NUM_COMMAND_SLOTS]);
+        }
+    }
+}
+
+/// Reads command frames off `socket` and coalesces outbound telemetry into a
+/// single buffered write per control tick rather than many small writes.
+async fn handle_connection(socket: TcpStream, last_frame: Arc<Mutex<Instant>>) -> Result<()> {
+    let (mut reader, writer) = socket.into_split();
+    let mut writer = BufWriter::new(writer);
+    let mut buf = [0u8; FRAME_BYTES];
+
+    loop {
+        reader.read_exact(&mut buf).await?;
+        *last_frame.lock().await = Instant::now();
+
+        let mut commands = [0.0f32; NUM_COMMAND_SLOTS];
+        for (slot, chunk) in commands.iter_mut().zip(buf.chunks_exact(4)) {
+            *slot = f32::from_le_bytes(chunk.try_into().expect("chunk is 4 bytes"));
+        }
+        keyboard::set_all_commands(commands);
+
+        // Echo the applied commands back as telemetry in one buffered write,
+        // rather than flushing a small write per field.
+        for value in commands {
+            writer.write_all(&value.to_le_bytes()).await?;
+        }
+        writer.flush().await?;
+    }
+}